@@ -1,18 +1,28 @@
 use super::*;
 
 use std::rc::Rc;
+use std::cell::RefCell;
+
+// the arms of a function taking the same number of arguments, with what each one matches
+type Arms<'a> = Vec<(&'a Arm, Vec<Pattern>)>;
 
 pub struct Checker {
-    ast: Vec<Statement>
+    ast:      Vec<Statement>,
+    warnings: RefCell<Vec<Diagnostic>>,
 }
 
 impl Checker {
     pub fn new(ast: Vec<Statement>) -> Checker {
         Checker {
             ast,
+            warnings: RefCell::new(Vec::new()),
         }
     }
 
+    pub fn warnings(&self) -> Diagnostics {
+        self.warnings.borrow().clone()
+    }
+
     pub fn check(&self, sym: &mut SymTab) -> CheckResult<()> {
         for statement in &self.ast {
             self.check_statement(sym, &statement)?
@@ -32,7 +42,7 @@ impl Checker {
 
             Expression::Identifier(ref id, ref position) => match sym.get_name(&*id) {
                 None    => {
-                    Err(CheckError::new_pos("undeclared use", position.clone()))
+                    Err(Diagnostic::error(UNDECLARED_USE, Span::width(*position, id.chars().count()), &format!("undeclared use: {}", id)))
                 },
                 Some(_) => Ok(())
             },
@@ -42,12 +52,18 @@ impl Checker {
                 self.check_expression(sym, &operation.right)
             },
 
+            Expression::Unary(ref unary) => self.check_expression(sym, &unary.operand),
+
             Expression::Function(ref function)   => {
+                self.check_arms(function);
+
                 match *function.arms {
                     Expression::Block(ref content) => for arm in content.iter() {
                         match *arm {
                             Statement::Expression(ref expression) => match **expression {
                                 Expression::Arm(ref arm) => {
+                                    self.check_overload(arm)?;
+
                                     let mut param_names = Vec::new();
 
                                     for p in &arm.params {
@@ -101,9 +117,109 @@ impl Checker {
                     
                     _ => (),
                 }
-            }
+            },
+            Statement::Fixity(_) => (),
         }
 
         Ok(())
     }
+
+    // operator arms must overload something lua has a metamethod for, with the operand it takes
+    fn check_overload(&self, arm: &Arm) -> CheckResult<()> {
+        let op = match arm.operand() {
+            Some(op) => op,
+            None     => return Ok(()),
+        };
+
+        let span = Span::width(arm.position, 1);
+
+        // declared operators have no metamethod, they're looked up by their symbol
+        let supported = match *op {
+            Operand::Infix(_) => arm.params.len() != 1,
+            _                 => op.metamethod(arm.params.len() == 1).is_some(),
+        };
+
+        if !supported {
+            let message = match *op {
+                Operand::NEqual  => "`~=` can't be overloaded, it's derived from `==`".to_string(),
+                Operand::Gt      => "`>` can't be overloaded, it's derived from `<`".to_string(),
+                Operand::GtEqual => "`>=` can't be overloaded, it's derived from `<=`".to_string(),
+                ref op if arm.params.len() == 1 => format!("`{}` is not a unary operator", op.symbol()),
+                ref op => format!("`{}` can't be overloaded", op.symbol()),
+            };
+
+            return Err(Diagnostic::error(UNSUPPORTED_OVERLOAD, span, &message))
+        }
+
+        if arm.params.len() > 2 {
+            return Err(Diagnostic::error(UNSUPPORTED_OVERLOAD, span, &format!("`{}` takes one operand, found {}", op.symbol(), arm.params.len() - 1)))
+        }
+
+        match arm.params.get(1).map(|p| &**p) {
+            None | Some(&Expression::Identifier(..)) => Ok(()),
+            Some(_) => Err(Diagnostic::error(UNSUPPORTED_OVERLOAD, span, "the operand of an operator arm must be a name")),
+        }
+    }
+
+    // warns about arms shadowed by earlier ones, and arities where some call matches no arm
+    fn check_arms(&self, function: &Function) {
+        let mut arities: Vec<(usize, Arms)> = Vec::new();
+
+        if let Expression::Block(ref content) = *function.arms {
+            for statement in content {
+                let arm = match *statement {
+                    Statement::Expression(ref e) => match **e {
+                        Expression::Arm(ref arm) => arm,
+                        _                        => continue,
+                    },
+                    _ => continue,
+                };
+
+                // operator arms become metamethods, they aren't part of the call
+                if arm.operand().is_some() {
+                    continue
+                }
+
+                let patterns = match arm.params.iter().map(|p| Pattern::from_param(p)).collect::<Option<Vec<Pattern>>>() {
+                    Some(patterns) => patterns,
+                    None           => continue,
+                };
+
+                let arity = patterns.len();
+
+                match arities.iter_mut().find(|a| a.0 == arity) {
+                    Some(a) => a.1.push((arm, patterns)),
+                    None    => arities.push((arity, vec![(arm, patterns)])),
+                }
+            }
+        }
+
+        for &(arity, ref group) in &arities {
+            let mut rows: Vec<Vec<Pattern>> = Vec::new();
+
+            for &(arm, ref patterns) in group {
+                if !useful(&rows, patterns) {
+                    let mut warning = Diagnostic::warning(UNREACHABLE_ARM, Span::width(arm.position, 1), "unreachable arm, earlier arms match everything it does");
+
+                    let catch_all = group.iter()
+                                         .take(rows.len())
+                                         .find(|(_, p)| p.iter().all(|p| *p == Pattern::Any));
+
+                    if let Some(&(shadow, _)) = catch_all {
+                        warning = warning.label(Span::width(shadow.position, 1), "this arm matches first")
+                    }
+
+                    self.warnings.borrow_mut().push(warning)
+                }
+
+                rows.push(patterns.clone())
+            }
+
+            if useful(&rows, &vec![Pattern::Any; arity]) {
+                let message = format!("non-exhaustive arms, some calls with {} argument{} match no arm", arity, if arity == 1 { "" } else { "s" });
+
+                self.warnings.borrow_mut().push(Diagnostic::warning(NON_EXHAUSTIVE, Span::width(function.position, 1), &message))
+            }
+        }
+    }
 }
@@ -1,30 +1,9 @@
 use super::*;
-use std::fmt;
 
-#[derive(Debug)]
-pub enum CheckErrorValue {
-    Constant(String),
-}
+// checker diagnostic codes
+// `E` codes are errors and `W` codes are warnings
 
-#[derive(Debug)]
-pub struct CheckError {
-    pub value: CheckErrorValue,
-    pub position: Option<TokenPosition>,
-}
-
-impl CheckError {
-    pub fn new_pos(value: &str, position: TokenPosition) -> CheckError {
-        CheckError {
-            value:    CheckErrorValue::Constant(value.to_owned()),
-            position: Some(position),
-        }
-    }
-}
-
-impl fmt::Display for CheckError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.value {
-            CheckErrorValue::Constant(ref s) => write!(f, "{}", s),
-        }
-    }
-}
+pub const UNDECLARED_USE:       Code = Code::new(Phase::Checker, "E0200");
+pub const NON_EXHAUSTIVE:       Code = Code::new(Phase::Checker, "W0201");
+pub const UNREACHABLE_ARM:      Code = Code::new(Phase::Checker, "W0202");
+pub const UNSUPPORTED_OVERLOAD: Code = Code::new(Phase::Checker, "E0203");
@@ -0,0 +1,70 @@
+use super::*;
+
+// what an arm parameter matches: anything, when it binds a name, or one literal value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Any,
+    Literal(String),
+}
+
+impl Pattern {
+    pub fn from_param(param: &Expression) -> Option<Pattern> {
+        match *param {
+            Expression::Identifier(..) => Some(Pattern::Any),
+            Expression::Number(ref n)  => Some(Pattern::Literal(format!("number {}", n))),
+            // `1.0` and `1` are equal in lua
+            Expression::Float(ref n)   => Some(Pattern::Literal(format!("number {}", n))),
+            Expression::Str(ref s)     => Some(Pattern::Literal(format!("string {:?}", s))),
+            Expression::Bool(ref b)    => Some(Pattern::Literal(format!("bool {}", b))),
+            Expression::Char(ref c)    => Some(Pattern::Literal(format!("char {:?}", c))),
+            _                          => None,
+        }
+    }
+}
+
+// the literals that together match every possible value, only booleans have a finite set
+fn complete(literals: &[String]) -> bool {
+    literals.contains(&"bool true".to_string()) && literals.contains(&"bool false".to_string())
+}
+
+// the rows that still apply once the first value is known to be `literal`, without that column
+fn specialize(rows: &[Vec<Pattern>], literal: &str) -> Vec<Vec<Pattern>> {
+    rows.iter().filter(|row| match row[0] {
+        Pattern::Any            => true,
+        Pattern::Literal(ref l) => l == literal,
+    }).map(|row| row[1 ..].to_vec()).collect()
+}
+
+// whether some input matched by `arm` is not matched by any of `rows`
+pub fn useful(rows: &[Vec<Pattern>], arm: &[Pattern]) -> bool {
+    if arm.is_empty() {
+        return rows.is_empty()
+    }
+
+    match arm[0] {
+        Pattern::Literal(ref literal) => useful(&specialize(rows, literal), &arm[1 ..]),
+
+        Pattern::Any => {
+            let mut literals = Vec::new();
+
+            for row in rows {
+                if let Pattern::Literal(ref l) = row[0] {
+                    if !literals.contains(l) {
+                        literals.push(l.clone())
+                    }
+                }
+            }
+
+            if complete(&literals) {
+                literals.iter().any(|l| useful(&specialize(rows, l), &arm[1 ..]))
+            } else {
+                let rest: Vec<Vec<Pattern>> = rows.iter()
+                                                  .filter(|row| row[0] == Pattern::Any)
+                                                  .map(|row| row[1 ..].to_vec())
+                                                  .collect();
+
+                useful(&rest, &arm[1 ..])
+            }
+        },
+    }
+}
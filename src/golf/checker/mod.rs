@@ -1,11 +1,13 @@
-pub mod error;
+mod error;
 pub mod symtab;
+pub mod matching;
 pub mod checker;
 
 pub use super::*;
 
 pub use self::error::*;
 pub use self::symtab::*;
+pub use self::matching::*;
 pub use self::checker::*;
 
-pub type CheckResult<T> = Result<T, CheckError>;
+pub type CheckResult<T> = Result<T, Diagnostic>;
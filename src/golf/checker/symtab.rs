@@ -4,10 +4,14 @@ use std::collections::HashMap;
 
 use std::fmt;
 
+use super::*;
+
 #[derive(Clone)]
 pub struct SymTab {
-    pub parent: Option<Rc<SymTab>>,
-    pub names:  RefCell<HashMap<String, usize>>,
+    pub parent:   Option<Rc<SymTab>>,
+    pub names:    RefCell<HashMap<String, usize>>,
+    // the operators declared so far, only kept in the global scope, for input compiled piece by piece
+    pub fixities: RefCell<Vec<Fixity>>,
 }
 
 impl SymTab {
@@ -19,15 +23,17 @@ impl SymTab {
         }
 
         SymTab {
-            parent: Some(parent),
-            names:  RefCell::new(hash_names),
+            parent:   Some(parent),
+            names:    RefCell::new(hash_names),
+            fixities: RefCell::new(Vec::new()),
         }
     }
 
     pub fn new_global() -> SymTab {
         SymTab {
-            parent: None,
-            names:  RefCell::new(HashMap::new()),
+            parent:   None,
+            names:    RefCell::new(HashMap::new()),
+            fixities: RefCell::new(Vec::new()),
         }
     }
 
@@ -57,6 +63,21 @@ impl SymTab {
         }
     }
 
+    // every name this scope and the ones around it can see
+    pub fn visible(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.names.borrow().keys().cloned().collect();
+
+        if let Some(ref parent) = self.parent {
+            for name in parent.visible() {
+                if !names.contains(&name) {
+                    names.push(name)
+                }
+            }
+        }
+
+        names
+    }
+
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
@@ -0,0 +1,100 @@
+use super::*;
+
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    // declare the globals of the embedded lua vm, e.g. `print`
+    pub prelude:      bool,
+    // emit top-level bindings as lua globals, see `Transpiler::new_global`
+    pub global:       bool,
+    // map the generated lua back to the golf source
+    pub source_map:   bool,
+    // raise an error when a call matches none of a function's arms, on by default in debug builds
+    pub match_errors: bool,
+    // the lua version the output runs on
+    pub target:       Target,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            prelude:      false,
+            global:       false,
+            source_map:   false,
+            match_errors: cfg!(debug_assertions),
+            target:       Target::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub ast:        Vec<Statement>,
+    pub lua:        Rc<String>,
+    pub source_map: Option<SourceMap>,
+    pub warnings:   Diagnostics,
+}
+
+pub fn compile(source: &str, options: &Options) -> Result<Output, Diagnostics> {
+    let mut symtab = if options.prelude {
+        prelude()
+    } else {
+        SymTab::new_global()
+    };
+
+    compile_in(source, options, &mut symtab)
+}
+
+// just the syntax tree, without checking or transpiling it
+pub fn parse(source: &str) -> Result<Vec<Statement>, Diagnostics> {
+    lower(&concrete(source)?)
+}
+
+// checks against a scope that outlives the call, for compiling input piece by piece
+//
+// the scope only takes in this input's names and operators once all of it compiles
+pub fn compile_in(source: &str, options: &Options, symtab: &mut SymTab) -> Result<Output, Diagnostics> {
+    let fixities = symtab.fixities.borrow().clone();
+
+    let ast = lower_with(&concrete_with(source, &fixities)?, &fixities)?;
+
+    let mut scope = symtab.clone();
+    let checker   = Checker::new(ast.clone());
+
+    if let Err(err) = checker.check(&mut scope) {
+        let mut diagnostics = checker.warnings();
+        diagnostics.push(err);
+
+        return Err(diagnostics)
+    }
+
+    let warnings = checker.warnings();
+
+    // declared operators carry over to the next input too
+    for statement in &ast {
+        if let Statement::Fixity(ref fixity) = *statement {
+            scope.fixities.borrow_mut().push(fixity.clone())
+        }
+    }
+
+    let mut transpiler = if options.global {
+        Transpiler::new_global(ast.clone())
+    } else {
+        Transpiler::new(ast.clone())
+    };
+
+    transpiler.set_match_errors(options.match_errors);
+    transpiler.set_target(options.target);
+
+    let (lua, source_map) = if options.source_map {
+        let (lua, source_map) = transpiler.lua_mapped().map_err(|err| vec![err])?;
+        (lua, Some(source_map))
+    } else {
+        (transpiler.lua().map_err(|err| vec![err])?, None)
+    };
+
+    *symtab = scope;
+
+    Ok(Output { ast, lua, source_map, warnings })
+}
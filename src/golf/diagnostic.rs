@@ -0,0 +1,174 @@
+use super::*;
+
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Error   => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note    => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Phase {
+    Lexer,
+    Parser,
+    Checker,
+    Transpiler,
+    Formatter,
+    Io,
+    Manifest,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Phase::Lexer      => write!(f, "lexer"),
+            Phase::Parser     => write!(f, "parser"),
+            Phase::Checker    => write!(f, "checker"),
+            Phase::Transpiler => write!(f, "transpiler"),
+            Phase::Formatter  => write!(f, "formatter"),
+            Phase::Io         => write!(f, "io"),
+            Phase::Manifest   => write!(f, "manifest"),
+        }
+    }
+}
+
+// a stable identifier for a kind of diagnostic, and the phase reporting it
+//
+// each phase lists its codes in an `error` module, and once released a code keeps its meaning, so they're safe to match on
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Code {
+    pub phase: Phase,
+    pub id:    &'static str,
+}
+
+impl Code {
+    pub const fn new(phase: Phase, id: &'static str) -> Code {
+        Code {
+            phase, id,
+        }
+    }
+}
+
+// from `start` up to, not including, `end`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Span {
+    pub start: TokenPosition,
+    pub end:   TokenPosition,
+}
+
+impl Span {
+    pub fn new(start: TokenPosition, end: TokenPosition) -> Span {
+        Span {
+            start, end,
+        }
+    }
+
+    pub fn width(start: TokenPosition, width: usize) -> Span {
+        Span::new(start, TokenPosition::new(start.line, start.col + width))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span:    Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code:     Code,
+    pub message:  String,
+    pub span:     Option<Span>,
+    pub labels:   Vec<Label>,
+}
+
+pub type Diagnostics = Vec<Diagnostic>;
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: Code, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            message: message.to_owned(),
+            span:    None,
+            labels:  Vec::new(),
+        }
+    }
+
+    pub fn error(code: Code, span: Span, message: &str) -> Diagnostic {
+        Diagnostic {
+            span: Some(span),
+            .. Diagnostic::new(Severity::Error, code, message)
+        }
+    }
+
+    pub fn warning(code: Code, span: Span, message: &str) -> Diagnostic {
+        Diagnostic {
+            span: Some(span),
+            .. Diagnostic::new(Severity::Warning, code, message)
+        }
+    }
+
+    pub fn label(mut self, span: Span, message: &str) -> Diagnostic {
+        self.labels.push(Label { span, message: message.to_owned() });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    // a single line of json, with 1-based lines and columns
+    pub fn to_json(&self, file: &str) -> String {
+        let span = |span: &Span| json!({
+            "line":       span.start.line,
+            "column":     span.start.col + 1,
+            "end_line":   span.end.line,
+            "end_column": span.end.col + 1,
+        });
+
+        // the same keys every time, null where there's no span
+        let position = |key: &str| self.span.map(|s| span(&s)[key].clone()).unwrap_or_default();
+
+        let value = json!({
+            "file":       file,
+            "line":       position("line"),
+            "column":     position("column"),
+            "end_line":   position("end_line"),
+            "end_column": position("end_column"),
+            "phase":      self.code.phase.to_string(),
+            "severity":   self.severity.to_string(),
+            "code":       self.code.id,
+            "message":    self.message,
+            "labels":     self.labels.iter().map(|l| {
+                let mut label = span(&l.span);
+                label["message"] = json!(l.message);
+                label
+            }).collect::<Vec<_>>(),
+        });
+
+        value.to_string()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(ref span) => write!(f, "{}[{}]: {}: {}", self.severity, self.code.id, span.start, self.message),
+            None           => write!(f, "{}[{}]: {}", self.severity, self.code.id, self.message),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use super::*;
+
+use std::rc::Rc;
+
+// a documented top-level binding, with the patterns of its arms when it's bound to a function
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub name: String,
+    pub doc:  Option<Rc<String>>,
+    pub arms: Vec<ArmDoc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArmDoc {
+    pub pattern: String,
+    pub doc:     Option<Rc<String>>,
+}
+
+// what one source file documents, `name` being its path from the project root without `.golf`
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name:  String,
+    pub items: Vec<Item>,
+}
+
+impl Module {
+    pub fn new(name: &str, ast: &[Statement]) -> Module {
+        let mut items = Vec::new();
+
+        for statement in ast {
+            let assignment = match *statement {
+                Statement::Assignment(ref assignment) => assignment,
+                _                                     => continue,
+            };
+
+            let name = match *assignment.left {
+                Expression::Identifier(ref name, _) => name.to_string(),
+                _                                   => continue,
+            };
+
+            let arms: Vec<ArmDoc> = match *assignment.right {
+                Expression::Function(ref function) => match *function.arms {
+                    Expression::Block(ref statements) => statements.iter().filter_map(|s| match *s {
+                        Statement::Expression(ref e) => match **e {
+                            Expression::Arm(ref arm) => Some(ArmDoc { pattern: pattern(arm), doc: arm.doc.clone() }),
+                            _                        => None,
+                        },
+                        _ => None,
+                    }).collect(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+
+            // only what's documented, on the binding itself or on one of its arms
+            if assignment.doc.is_some() || arms.iter().any(|a| a.doc.is_some()) {
+                items.push(Item { name, doc: assignment.doc.clone(), arms })
+            }
+        }
+
+        Module {
+            name: name.to_owned(),
+            items,
+        }
+    }
+}
+
+// an arm as it's written, `|x "y" 0|`
+pub fn pattern(arm: &Arm) -> String {
+    let params: Vec<String> = arm.params.iter().map(|p| match **p {
+        Expression::Identifier(ref id, _) => id.to_string(),
+        Expression::Number(ref n)         => n.to_string(),
+        Expression::Float(ref n)          => format!("{:?}", n),
+        Expression::Bool(ref b)           => b.to_string(),
+        Expression::Str(ref s)            => format!("\"{}\"", s),
+        Expression::Char(ref c)           => format!("'{}'", c),
+        Expression::Operand(ref op)       => op.symbol().to_string(),
+        _                                 => "_".to_string(),
+    }).collect();
+
+    format!("|{}|", params.join(" "))
+}
+
+// which module documents each name, so `name` in doc text can link to it
+pub struct Index {
+    names: Vec<(String, String)>,
+}
+
+impl Index {
+    pub fn new(modules: &[Module]) -> Index {
+        let mut names = Vec::new();
+
+        for module in modules {
+            for item in &module.items {
+                names.push((item.name.clone(), module.name.clone()))
+            }
+        }
+
+        Index {
+            names,
+        }
+    }
+
+    // the link to `name` from the page of module `from`, preferring a definition in `from` itself
+    fn link(&self, from: &str, name: &str, extension: &str) -> Option<String> {
+        let module = self.names.iter()
+                               .find(|n| n.0 == name && n.1 == from)
+                               .or_else(|| self.names.iter().find(|n| n.0 == name))
+                               .map(|n| &n.1)?;
+
+        if module == from {
+            return Some(format!("#{}", name))
+        }
+
+        Some(format!("{}{}.{}#{}", relative(from), module, extension, name))
+    }
+}
+
+// from the page of module `from` back up to the documentation root
+fn relative(from: &str) -> String {
+    "../".repeat(from.matches('/').count())
+}
+
+// splits doc text around `code` spans, linking the ones naming a documented binding
+fn linked<F, G>(text: &str, mut plain: F, mut code: G) -> String where F: FnMut(&str) -> String, G: FnMut(&str) -> String {
+    let mut result = String::new();
+
+    for (i, part) in text.split('`').enumerate() {
+        if i % 2 == 0 {
+            result.push_str(&plain(part))
+        } else {
+            result.push_str(&code(part))
+        }
+    }
+
+    result
+}
+
+pub fn markdown(module: &Module, index: &Index) -> String {
+    let mut result = format!("# {}\n", module.name);
+
+    let text = |doc: &str| linked(doc, |plain| plain.to_owned(), |code| match index.link(&module.name, code, "md") {
+        Some(link) => format!("[`{}`]({})", code, link),
+        None       => format!("`{}`", code),
+    });
+
+    for item in &module.items {
+        result.push_str(&format!("\n<a id=\"{0}\"></a>\n\n## {0}\n", item.name));
+
+        if let Some(ref doc) = item.doc {
+            result.push_str(&format!("\n{}\n", text(doc)))
+        }
+
+        if !item.arms.is_empty() {
+            result.push('\n');
+
+            for arm in &item.arms {
+                match arm.doc {
+                    Some(ref doc) => result.push_str(&format!("- `{}` {}\n", arm.pattern, text(&doc.replace('\n', " ")))),
+                    None          => result.push_str(&format!("- `{}`\n", arm.pattern)),
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!("<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n", escape(title), body)
+}
+
+pub fn html(module: &Module, index: &Index) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape(&module.name));
+
+    let text = |doc: &str| linked(doc, escape, |code| match index.link(&module.name, code, "html") {
+        Some(link) => format!("<a href=\"{}\"><code>{}</code></a>", escape(&link), escape(code)),
+        None       => format!("<code>{}</code>", escape(code)),
+    });
+
+    for item in &module.items {
+        body.push_str(&format!("<h2 id=\"{0}\">{0}</h2>\n", escape(&item.name)));
+
+        if let Some(ref doc) = item.doc {
+            for paragraph in doc.split("\n\n") {
+                body.push_str(&format!("<p>{}</p>\n", text(paragraph)))
+            }
+        }
+
+        if !item.arms.is_empty() {
+            body.push_str("<ul>\n");
+
+            for arm in &item.arms {
+                let doc = match arm.doc {
+                    Some(ref doc) => format!(" {}", text(doc)),
+                    None          => String::new(),
+                };
+
+                body.push_str(&format!("<li><code>{}</code>{}</li>\n", escape(&arm.pattern), doc))
+            }
+
+            body.push_str("</ul>\n");
+        }
+    }
+
+    page(&module.name, &body)
+}
+
+// the front page, listing every module and what it documents
+pub fn markdown_index(modules: &[Module]) -> String {
+    let mut result = "# modules\n\n".to_string();
+
+    for module in modules {
+        let names: Vec<String> = module.items.iter().map(|i| format!("[`{1}`]({0}.md#{1})", module.name, i.name)).collect();
+
+        result.push_str(&format!("- [{0}]({0}.md) {1}\n", module.name, names.join(", ")))
+    }
+
+    result
+}
+
+pub fn html_index(modules: &[Module]) -> String {
+    let mut body = "<h1>modules</h1>\n<ul>\n".to_string();
+
+    for module in modules {
+        let names: Vec<String> = module.items.iter().map(|i| format!("<a href=\"{0}.html#{1}\"><code>{1}</code></a>", escape(&module.name), escape(&i.name))).collect();
+
+        body.push_str(&format!("<li><a href=\"{0}.html\">{0}</a> {1}</li>\n", escape(&module.name), names.join(", ")))
+    }
+
+    body.push_str("</ul>\n");
+
+    page("modules", &body)
+}
@@ -0,0 +1,11 @@
+use super::*;
+
+// diagnostic codes of the phases that are a single module, the others keep theirs in their own `error` module
+
+// a change `golf fmt` would make reads differently from the source
+pub const CHANGED_MEANING:  Code = Code::new(Phase::Formatter, "E0401");
+
+// reading or writing a file failed, reported by whatever was doing it
+pub const IO_ERROR:         Code = Code::new(Phase::Io, "E0501");
+
+pub const INVALID_MANIFEST: Code = Code::new(Phase::Manifest, "E0601");
@@ -0,0 +1,290 @@
+use super::*;
+
+// a token and its exact source text, so strings and comments are printed as written
+struct Piece {
+    token: Token,
+    text:  String,
+}
+
+impl Piece {
+    fn is(&self, token_type: TokenType, content: &str) -> bool {
+        self.token.token_type == token_type && self.token.content() == content
+    }
+
+    fn is_bar(&self) -> bool {
+        self.is(TokenType::Symbol, "|")
+    }
+
+    fn is_comment(&self) -> bool {
+        matches!(self.token.token_type, TokenType::Comment | TokenType::DocComment)
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(self.token.token_type, TokenType::IntLiteral | TokenType::FloatLiteral)
+    }
+
+    fn opens(&self) -> bool {
+        self.token.token_type == TokenType::Symbol && ["{", "(", "["].contains(&self.token.content().as_str())
+    }
+
+    fn closes(&self) -> bool {
+        self.token.token_type == TokenType::Symbol && ["}", ")", "]"].contains(&self.token.content().as_str())
+    }
+
+    // whether an operator right after this is binary rather than prefix
+    fn ends_value(&self) -> bool {
+        match self.token.token_type {
+            TokenType::IntLiteral
+          | TokenType::FloatLiteral
+          | TokenType::StringLiteral
+          | TokenType::CharLiteral
+          | TokenType::BoolLiteral
+          | TokenType::Identifier => true,
+            TokenType::Symbol     => self.closes() || self.token.content() == "!",
+            _                     => false,
+        }
+    }
+}
+
+// an open `{`, `(` or `[`, hanging when the arm last started in it has its body on the following lines
+struct Frame {
+    hang: bool,
+}
+
+// a printed line, with where the body starts when it's an arm with its body on the same line
+struct Line {
+    indent: usize,
+    text:   String,
+    body:   Option<usize>,
+}
+
+// the source in the canonical layout, keeping comments and single blank lines
+pub fn format(source: &str) -> Result<String, Diagnostics> {
+    let before = parse(source)?;
+
+    let lines = pieces(source)?;
+
+    let mut frames  = vec![Frame { hang: false }];
+    let mut printed = Vec::new();
+
+    for line in &lines {
+        if line.is_empty() {
+            printed.push(None);
+            continue
+        }
+
+        let closers = line.iter().take_while(|p| p.closes()).count();
+
+        for _ in 0 .. closers {
+            if frames.len() > 1 {
+                frames.pop();
+            }
+        }
+
+        if line[0].is_bar() {
+            frames.last_mut().unwrap().hang = false
+        }
+
+        let mut indent = frames.len() - 1 + frames.iter().filter(|f| f.hang).count();
+
+        // a line continuing the expression above, like `|> f`
+        if line[0].token.token_type == TokenType::Operator && !["-", "not"].contains(&line[0].token.content().as_str()) {
+            indent += 1
+        }
+
+        for piece in &line[closers ..] {
+            if piece.opens() {
+                frames.push(Frame { hang: false })
+            } else if piece.closes() && frames.len() > 1 {
+                frames.pop();
+            }
+        }
+
+        let (text, body) = render(line);
+
+        let code: Vec<&Piece> = line.iter().filter(|p| !p.is_comment()).collect();
+
+        // a lone `|a b|`, the lines below are its body
+        if body.is_none() && code.len() > 1 && code[0].is_bar() && code[code.len() - 1].is_bar() {
+            frames.last_mut().unwrap().hang = true
+        }
+
+        printed.push(Some(Line { indent, text, body }))
+    }
+
+    align(&mut printed);
+
+    let mut output = String::new();
+    let mut blank  = false;
+
+    for line in &printed {
+        match *line {
+            Some(ref line) => {
+                if blank && !output.is_empty() {
+                    output.push('\n')
+                }
+
+                blank = false;
+
+                for _ in 0 .. line.indent {
+                    output.push_str("  ")
+                }
+
+                output.push_str(&line.text);
+                output.push('\n')
+            },
+
+            None => blank = true,
+        }
+    }
+
+    // printed differently but compiling differently too would be a formatter bug, so nothing is changed
+    let after = parse(&output).map_err(|_| vec![changed()])?;
+
+    let lua = |ast: Vec<Statement>| Transpiler::new(ast).lua().map(|lua| lua.to_string());
+
+    match (lua(before), lua(after)) {
+        (Ok(ref a), Ok(ref b)) if a == b => Ok(output),
+        (Err(err), _)                    => Err(vec![err]),
+        _                                => Err(vec![changed()]),
+    }
+}
+
+fn changed() -> Diagnostic {
+    Diagnostic::new(Severity::Error, CHANGED_MEANING, "formatting would change what this code does, so it was left as is")
+}
+
+// the source split into lines of tokens and comments, without the whitespace between them
+fn pieces(source: &str) -> Result<Vec<Vec<Piece>>, Diagnostics> {
+    let root = concrete(source)?;
+
+    let mut lines = vec![Vec::new()];
+
+    for token in &root.tokens {
+        let comments = |trivia: &Vec<Trivia>| {
+            trivia.iter()
+                  .filter(|t| t.token.token_type == TokenType::Comment || t.token.token_type == TokenType::DocComment)
+                  .map(|t| Piece { token: t.token.clone(), text: t.text.clone() })
+                  .collect::<Vec<_>>()
+        };
+
+        lines.last_mut().unwrap().extend(comments(&token.leading));
+
+        match token.token.token_type {
+            TokenType::EOL                     => lines.push(Vec::new()),
+            TokenType::Indent | TokenType::EOF => (),
+            _                                  => lines.last_mut().unwrap().push(Piece { token: token.token.clone(), text: token.text.clone() }),
+        }
+
+        lines.last_mut().unwrap().extend(comments(&token.trailing));
+    }
+
+    Ok(lines)
+}
+
+// one line with single spaces between tokens, except where they are glued like `f!`, `(x)`, `a[k]`, `|x|` and `-1`
+fn render(line: &[Piece]) -> (String, Option<usize>) {
+    let mut text   = String::new();
+    let mut header = false;
+    let mut prefix = false;
+    let mut body   = None;
+
+    for (i, piece) in line.iter().enumerate() {
+        if i > 0 && !prefix && spaced(&line[i - 1], piece, header) {
+            text.push(' ')
+        }
+
+        let next = line.get(i + 1);
+
+        prefix = piece.is(TokenType::Operator, "-") && (i == 0 || !line[i - 1].ends_value()) && match next {
+            // `|- v|` overloads minus, `|-1|` matches a negative number
+            Some(next) if header => next.is_number(),
+            // `- -x` mustn't become a comment
+            Some(next)           => !next.text.starts_with('-'),
+            None                 => false,
+        };
+
+        text.push_str(&piece.text);
+
+        if piece.is_bar() {
+            header = !header;
+
+            if !header && body.is_none() && line[0].is_bar() && next.is_some_and(|n| !n.is_comment()) {
+                body = Some(text.len())
+            }
+        }
+    }
+
+    (text, body)
+}
+
+fn spaced(prev: &Piece, next: &Piece, header: bool) -> bool {
+    if next.is_comment() {
+        return true
+    }
+
+    if prev.is(TokenType::Symbol, "(") || prev.is(TokenType::Symbol, "[") {
+        return false
+    }
+
+    if next.token.token_type == TokenType::Symbol && [")", "]", ",", "!"].contains(&next.token.content().as_str()) {
+        return false
+    }
+
+    if prev.is(TokenType::Symbol, "{") && next.is(TokenType::Symbol, "}") {
+        return false
+    }
+
+    // indexing
+    if next.is(TokenType::Symbol, "[") && prev.ends_value() {
+        return false
+    }
+
+    // inside the bars of an arm
+    !(header && (prev.is_bar() || next.is_bar()))
+}
+
+// lines up the bodies of neighbouring arms written on one line each
+fn align(lines: &mut [Option<Line>]) {
+    let mut start = 0;
+
+    while start < lines.len() {
+        let mut end = start;
+
+        while end < lines.len() && arm(&lines[end]).is_some() && arm(&lines[end]) == arm(&lines[start]) {
+            end += 1
+        }
+
+        if end == start {
+            start += 1;
+            continue
+        }
+
+        let width = lines[start .. end].iter().map(|line| {
+            let line = line.as_ref().unwrap();
+            line.text[.. line.body.unwrap()].chars().count()
+        }).max().unwrap();
+
+        for line in &mut lines[start .. end] {
+            let line = line.as_mut().unwrap();
+            let at   = line.body.unwrap();
+
+            let header = line.text[.. at].to_string();
+            let body   = line.text[at ..].trim_start().to_string();
+
+            let padding = width - header.chars().count() + 1;
+
+            line.text = format!("{}{}{}", header, " ".repeat(padding), body);
+        }
+
+        start = end
+    }
+}
+
+// the indentation of an arm with its body on the same line
+fn arm(line: &Option<Line>) -> Option<usize> {
+    match *line {
+        Some(ref line) if line.body.is_some() => Some(line.indent),
+        _                                     => None,
+    }
+}
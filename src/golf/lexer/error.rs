@@ -0,0 +1,10 @@
+use super::*;
+
+// lexer diagnostic codes
+
+pub const UNEXPECTED_CHARACTER: Code = Code::new(Phase::Lexer, "E0001");
+pub const INVALID_NUMBER:       Code = Code::new(Phase::Lexer, "E0002");
+pub const INVALID_ESCAPE:       Code = Code::new(Phase::Lexer, "E0003");
+pub const INVALID_CHAR:         Code = Code::new(Phase::Lexer, "E0004");
+pub const UNTERMINATED_STRING:  Code = Code::new(Phase::Lexer, "E0005");
+pub const UNTERMINATED_COMMENT: Code = Code::new(Phase::Lexer, "E0006");
@@ -1,9 +1,8 @@
-use super::Tokenizer;
-use super::matcher::*;
-use super::token::{Token, TokenType};
+use super::*;
 
 use std::str::Chars;
 use std::rc::Rc;
+use std::cell::RefCell;
 
 pub fn lexer(data: &mut Chars) -> Lexer {
     let tokenizer = Tokenizer::new(data);
@@ -32,7 +31,7 @@ pub fn lexer(data: &mut Chars) -> Lexer {
         "+",
         "-",
         "*",
-        "/",
+        "//",
         "/",
         "%",
         "^",
@@ -58,10 +57,26 @@ pub fn lexer(data: &mut Chars) -> Lexer {
 
     let matcher_eol            = ConstantMatcher::new(TokenType::EOL, eol);
     let matcher_indent         = ConstantMatcher::new(TokenType::Indent, indent);
-    let matcher_operator       = ConstantMatcher::new(TokenType::Operator, operators);
+    let operators = Rc::new(RefCell::new(operators));
+
+    let matcher_operator       = ConstantMatcher::shared(TokenType::Operator, operators.clone());
     let matcher_symbol         = ConstantMatcher::new(TokenType::Symbol, symbols);
+    let keywords = [
+        "and",
+        "not",
+        "or",
+        "band",
+        "bor",
+        "bxor",
+        "bnot",
+        "shl",
+        "shr",
+    ].iter().map(|&x| x.to_string()).collect();
+
     let matcher_boolean        = KeyMatcher::new(TokenType::BoolLiteral, boolean);
+    let matcher_keyword        = KeyMatcher::new(TokenType::Operator, keywords);
     let matcher_whitespace     = WhitespaceMatcher {};
+    let matcher_comment        = CommentMatcher {};
     let matcher_int_literal    = IntLiteralMatcher {};
     let matcher_float_literal  = FloatLiteralMatcher {};
     let matcher_identifier     = IdentifierMatcher {};
@@ -70,19 +85,32 @@ pub fn lexer(data: &mut Chars) -> Lexer {
     lexer.matchers_mut().push(Rc::new(matcher_eol));
     lexer.matchers_mut().push(Rc::new(matcher_indent));
     lexer.matchers_mut().push(Rc::new(matcher_whitespace));
+    lexer.matchers_mut().push(Rc::new(matcher_comment));
     lexer.matchers_mut().push(Rc::new(matcher_operator));
     lexer.matchers_mut().push(Rc::new(matcher_symbol));
     lexer.matchers_mut().push(Rc::new(matcher_float_literal));
     lexer.matchers_mut().push(Rc::new(matcher_int_literal));
     lexer.matchers_mut().push(Rc::new(matcher_string_literal));
     lexer.matchers_mut().push(Rc::new(matcher_boolean));
+    lexer.matchers_mut().push(Rc::new(matcher_keyword));
     lexer.matchers_mut().push(Rc::new(matcher_identifier));
+
+    lexer.declarations = Some(DeclarationMatcher::new(operators));
+
     lexer
 }
 
+// `infixl 6 <+>` declares `<+>` as an operator
+pub const FIXITY_KEYWORDS: &[&str] = &["infixl", "infixr"];
+
 pub struct Lexer {
     tokenizer: Tokenizer,
     matchers: Vec<Rc<Matcher>>,
+    // how far into a fixity declaration the last tokens were, the symbol comes third
+    declaring:    usize,
+    declarations: Option<DeclarationMatcher>,
+    // whether comments are kept as tokens, rather than skipped like whitespace
+    trivia:       bool,
 }
 
 #[allow(dead_code)]
@@ -90,18 +118,60 @@ impl Lexer {
     pub fn new(tokenizer: Tokenizer) -> Lexer {
         Lexer {
             tokenizer,
-            matchers: Vec::new(),
+            matchers:     Vec::new(),
+            declaring:    0,
+            declarations: None,
+            trivia:       false,
         }
     }
 
-    pub fn match_token(&mut self) -> Option<Token> {
+    // keeps comments, for tools working on the source as written
+    pub fn with_trivia(mut self) -> Lexer {
+        self.trivia = true;
+        self
+    }
+
+    // lexes `symbols` as operators from the start, for ones declared by earlier input
+    pub fn with_operators(self, symbols: &[&str]) -> Lexer {
+        if let Some(ref declarations) = self.declarations {
+            for symbol in symbols {
+                declarations.declare(symbol)
+            }
+        }
+
+        self
+    }
+
+    pub fn match_token(&mut self) -> LexResult<Option<Token>> {
+        if let Some(ref matcher) = self.declarations {
+            if self.declaring == 2 {
+                if let Some(t) = self.tokenizer.try_match_token(matcher)? {
+                    return Ok(Some(t))
+                }
+            }
+
+            // `|>>= f|` is an arm overloading `>>=`, not `|>` followed by `>=`
+            if self.tokenizer.peek() == Some(&'|') && matcher.declared_at(&self.tokenizer, 1) {
+                return self.tokenizer.try_match_token(&ConstantMatcher::new(TokenType::Symbol, vec!["|".to_string()]))
+            }
+        }
+
         for matcher in &mut self.matchers {
-            match self.tokenizer.try_match_token(matcher.as_ref()) {
-                Some(t) => return Some(t),
+            match self.tokenizer.try_match_token(matcher.as_ref())? {
+                Some(t) => return Ok(Some(t)),
                 None => continue,
             }
         }
-        None
+
+        match self.tokenizer.peek().cloned() {
+            Some(c) => {
+                let position = self.tokenizer.pos;
+                self.tokenizer.advance(1);
+
+                Err(Diagnostic::error(UNEXPECTED_CHARACTER, Span::width(position, 1), &format!("unexpected character: {}", c)))
+            },
+            None => Ok(None),
+        }
     }
 
     pub fn matchers(&self) -> &Vec<Rc<Matcher>> {
@@ -114,22 +184,28 @@ impl Lexer {
 }
 
 impl Iterator for Lexer {
-    type Item = Token;
+    type Item = LexResult<Token>;
 
-    fn next(&mut self) -> Option<Token> {
+    fn next(&mut self) -> Option<LexResult<Token>> {
         let token = match self.match_token() {
-            Some(n) => n,
-            None    => return None,
+            Ok(Some(n)) => n,
+            Ok(None)    => return None,
+            Err(err)    => return Some(Err(err)),
         };
         match token.token_type {
             TokenType::EOF => None,
-            TokenType::Whitespace => {
-                match self.next() {
-                    Some(t) => Some(t),
-                    None => None,
-                }
-            }
-            _ => Some(token),
+            TokenType::Whitespace => self.next(),
+            TokenType::Comment if !self.trivia => self.next(),
+            TokenType::Comment => Some(Ok(token)),
+            _ => {
+                self.declaring = match token.token_type {
+                    TokenType::Identifier if FIXITY_KEYWORDS.contains(&token.content().as_str()) => 1,
+                    TokenType::IntLiteral if self.declaring == 1 => 2,
+                    _ => 0,
+                };
+
+                Some(Ok(token))
+            },
         }
     }
 }
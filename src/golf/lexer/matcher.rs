@@ -1,24 +1,27 @@
 use super::*;
 
+use std::rc::Rc;
+use std::cell::RefCell;
+
 macro_rules! token {
     ($tokenizer:expr, $token_type:ident, $accum:expr) => {{
         token!($tokenizer , TokenType::$token_type, $accum)
     }};
     ($tokenizer:expr, $token_type:expr, $accum:expr) => {{
-        let tokenizer  = $tokenizer  as &$crate::golf::lexer::Tokenizer;
-        let token_type = $token_type as $crate::golf::lexer::token::TokenType;
-        Some(Token::new(token_type, tokenizer.last_position(), $accum))
+        let tokenizer  = $tokenizer  as &$crate::lexer::Tokenizer;
+        let token_type = $token_type as $crate::lexer::token::TokenType;
+        Ok(Some(Token::new(token_type, tokenizer.last_position(), $accum)))
     }};
 }
 
 pub trait Matcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token>;
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>>;
 }
 
 pub struct WhitespaceMatcher;
 
 impl Matcher for WhitespaceMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         let mut found = false;
         while !tokenizer.end() && tokenizer.peek().unwrap().is_whitespace() {
             found = true;
@@ -27,42 +30,90 @@ impl Matcher for WhitespaceMatcher {
         if found {
             token!(tokenizer, Whitespace, String::new())
         } else {
-            None
+            Ok(None)
+        }
+    }
+}
+
+// `-- to the end of the line`, or `--[[ across lines ]]` where block comments nest
+pub struct CommentMatcher;
+
+impl CommentMatcher {
+    fn at(tokenizer: &Tokenizer, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| tokenizer.peek_n(i) == Some(&c))
+    }
+}
+
+impl Matcher for CommentMatcher {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
+        if !CommentMatcher::at(tokenizer, "--") {
+            return Ok(None)
+        }
+
+        let mut comment = String::new();
+
+        if CommentMatcher::at(tokenizer, "--[[") {
+            let mut depth = 0;
+
+            loop {
+                if tokenizer.end() {
+                    return Err(Diagnostic::error(UNTERMINATED_COMMENT, Span::width(tokenizer.last_position(), 4), "unterminated block comment"))
+                }
+
+                if CommentMatcher::at(tokenizer, "--[[") {
+                    depth += 1;
+                    comment.push_str("--[[");
+                    tokenizer.advance(4);
+                } else if CommentMatcher::at(tokenizer, "]]") {
+                    depth -= 1;
+                    comment.push_str("]]");
+                    tokenizer.advance(2);
+
+                    if depth == 0 {
+                        break
+                    }
+                } else {
+                    comment.push(tokenizer.next().unwrap())
+                }
+            }
+        } else {
+            // the line break is left for the `EOL` token
+            while !tokenizer.end() && tokenizer.peek() != Some(&'\n') {
+                comment.push(tokenizer.next().unwrap())
+            }
+
+            // `--- ...` documents whatever is on the line below
+            if comment.starts_with("---") {
+                return token!(tokenizer, DocComment, comment)
+            }
         }
+
+        token!(tokenizer, Comment, comment)
+    }
+}
+
+// integers are lua's 64 bit ones, a literal past them would silently become a float
+fn int_literal(tokenizer: &Tokenizer, digits: &str) -> LexResult<String> {
+    match digits.parse::<i64>() {
+        Ok(result) => Ok(result.to_string()),
+        Err(_)     => Err(Diagnostic::error(INVALID_NUMBER, tokenizer.span(), &format!("integer `{}` is out of range, the largest is {}", digits, i64::MAX))),
     }
 }
 
 pub struct IntLiteralMatcher {}
 
 impl Matcher for IntLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         let mut accum = String::new();
-        let prefix = match tokenizer.peek() {
-            Some(&'-') => Some(false),
-            Some(&'+') => Some(true),
-            _          => None,
-        };
-        if let Some(_) = prefix {
-            tokenizer.advance(1)
-        };
-        while !tokenizer.end() && tokenizer.peek().unwrap().is_digit(10) {
+        while !tokenizer.end() && tokenizer.peek().unwrap().is_ascii_digit() {
             accum.push(tokenizer.next().unwrap());
         }
         if !accum.is_empty() {
-            let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
-                    Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse int: {}", error)
-                }
-            } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
-                    Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse int: {}", error)
-                }
-            };
+            // signs are left to the parser, as unary operators
+            let literal = int_literal(tokenizer, &accum)?;
             token!(tokenizer, IntLiteral, literal)
         } else {
-            None
+            Ok(None)
         }
     }
 }
@@ -70,32 +121,26 @@ impl Matcher for IntLiteralMatcher {
 pub struct FloatLiteralMatcher;
 
 impl Matcher for FloatLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         let mut accum = String::new();
 
-        let prefix = match tokenizer.peek() {
-            Some(&'-') => Some(false),
-            Some(&'+') => Some(true),
-            _          => None,
-        };
-
-        if let Some(_) = prefix {
-            tokenizer.advance(1)
+        let curr = match tokenizer.next() {
+            Some(c) => c,
+            None    => return Ok(None),
         };
-
-        let curr = tokenizer.next().unwrap();
-        if curr.is_digit(10) {
+        if curr.is_ascii_digit() {
             accum.push(curr)
         } else if curr == '.' {
             accum.push_str("0.")
         } else {
-            return None
+            return Ok(None)
         }
         while !tokenizer.end() {
             let current = *tokenizer.peek().unwrap();
-            if !current.is_whitespace() && current.is_digit(10) || current == '.' {
+            if !current.is_whitespace() && current.is_ascii_digit() || current == '.' {
                 if current == '.' && accum.contains('.') {
-                    panic!("illegal decimal point")
+                    tokenizer.advance(1);
+                    return Err(Diagnostic::error(INVALID_NUMBER, tokenizer.span(), "illegal decimal point"))
                 }
                 accum.push(tokenizer.next().unwrap())
             } else {
@@ -104,33 +149,16 @@ impl Matcher for FloatLiteralMatcher {
         }
 
         if accum == "0." {
-            None
+            Ok(None)
         } else if accum.contains('.') {
-            let literal: String = if Some(false) == prefix {
-                match accum.parse::<f64>() {
-                    Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse float: {}", error)
-                }
-            } else {
-                match accum.parse::<f64>() {
-                    Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse float: {}", error)
-                }
+            let literal = match accum.parse::<f64>() {
+                Ok(result) => result.to_string(),
+                Err(error) => return Err(Diagnostic::error(INVALID_NUMBER, tokenizer.span(), &format!("unable to parse float: {}", error)))
             };
 
             token!(tokenizer, FloatLiteral, literal)
         } else {
-            let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
-                    Ok(result) => format!("-{}", result),
-                    Err(error) => panic!("unable to parse int: {}", error)
-                }
-            } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
-                    Ok(result) => result.to_string(),
-                    Err(error) => panic!("unable to parse int: {}", error)
-                }
-            };
+            let literal = int_literal(tokenizer, &accum)?;
 
             token!(tokenizer, IntLiteral, literal)
         }
@@ -140,7 +168,7 @@ impl Matcher for FloatLiteralMatcher {
 pub struct StringLiteralMatcher {}
 
 impl Matcher for StringLiteralMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         let mut raw_marker = false;
         let delimeter  = match *tokenizer.peek().unwrap() {
             '"'  => Some('"'),
@@ -151,49 +179,64 @@ impl Matcher for StringLiteralMatcher {
 
                 Some('"')
             },
-            _ => return None,
+            _ => return Ok(None),
         };
         tokenizer.advance(1);
         let mut string       = String::new();
         let mut found_escape = false;
+        let mut terminated   = false;
+        let mut error        = None;
+        let mut escape_start = tokenizer.pos;
         while !tokenizer.end() {
             if raw_marker {
                 if tokenizer.peek().unwrap() == &'"' {
+                    terminated = true;
                     break
                 }
                 string.push(tokenizer.next().unwrap())
             } else if found_escape {
-                string.push(
-                    match tokenizer.next().unwrap() {
-                        c @ '\\' | c @ '\'' | c @ '"' => c,
-                        'n' => '\n',
-                        'r' => '\r',
-                        't' => '\t',
-                        s => panic!("invalid character escape: {}", s),
-                    }
-                );
+                match tokenizer.next().unwrap() {
+                    c @ '\\' | c @ '\'' | c @ '"' => string.push(c),
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    c   => if error.is_none() {
+                        // keep going to the closing delimiter, so the rest of the literal isn't lexed as code
+                        error = Some(Diagnostic::error(INVALID_ESCAPE, Span::new(escape_start, tokenizer.pos), &format!("invalid character escape: {}", c)))
+                    },
+                }
                 found_escape = false
             } else {
                 match *tokenizer.peek().unwrap() {
                     '\\' => {
+                        escape_start = tokenizer.pos;
                         tokenizer.next();
                         found_escape = true
                     },
-                    c if c == delimeter.unwrap() => break,
+                    c if c == delimeter.unwrap() => {
+                        terminated = true;
+                        break
+                    },
                     _ => string.push(tokenizer.next().unwrap()),
                 }
             }
         }
+        if !terminated {
+            return Err(Diagnostic::error(UNTERMINATED_STRING, Span::width(tokenizer.last_position(), 1), "unterminated string literal"))
+        }
         tokenizer.advance(1);
+        if let Some(error) = error {
+            return Err(error)
+        }
         match delimeter.unwrap() {
             '"'  => {
                 token!(tokenizer, StringLiteral, string)
             },
             _ => {
-                if string.len() == 1 {
+                if string.chars().count() == 1 {
                     token!(tokenizer, CharLiteral, string)
                 } else {
-                    panic!("invalid char literal")
+                    Err(Diagnostic::error(INVALID_CHAR, tokenizer.span(), "char literal must be exactly one character"))
                 }
             },
         }
@@ -202,31 +245,92 @@ impl Matcher for StringLiteralMatcher {
 
 pub struct ConstantMatcher {
     token_type: TokenType,
-    constants: Vec<String>,
+    constants: Rc<RefCell<Vec<String>>>,
 }
 
 impl ConstantMatcher {
     pub fn new(token_type: TokenType, constants: Vec<String>) -> Self {
+        ConstantMatcher::shared(token_type, Rc::new(RefCell::new(constants)))
+    }
+
+    // matches whatever `constants` holds at the time, for tables that grow while lexing
+    pub fn shared(token_type: TokenType, constants: Rc<RefCell<Vec<String>>>) -> Self {
         ConstantMatcher {
-            token_type: token_type,
-            constants: constants,
+            token_type,
+            constants,
         }
     }
 }
 
 impl Matcher for ConstantMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
-        for constant in self.constants.clone() {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
+        for constant in self.constants.borrow().iter() {
             let dat = tokenizer.clone().take(constant.len());
             if dat.size_hint().1.unwrap() != constant.len() {
-                return None
+                continue
             }
-            if dat.collect::<String>() == constant {
+            if dat.collect::<String>() == *constant {
                 tokenizer.advance(constant.len());
-                return token!(tokenizer, self.token_type.clone(), constant)
+                return token!(tokenizer, self.token_type.clone(), constant.clone())
             }
         }
-        None
+        Ok(None)
+    }
+}
+
+// the characters a declared operator is made of
+pub const OPERATOR_CHARS: &str = "!#$%&*+-./:<=>?@^|~";
+
+// the symbol of an `infixl` or `infixr` declaration, adding it to the operators matched from then on
+pub struct DeclarationMatcher {
+    operators: Rc<RefCell<Vec<String>>>,
+    declared:  RefCell<Vec<String>>,
+}
+
+impl DeclarationMatcher {
+    pub fn new(operators: Rc<RefCell<Vec<String>>>) -> Self {
+        DeclarationMatcher {
+            operators,
+            declared: RefCell::new(Vec::new()),
+        }
+    }
+
+    // whether a declared operator starts `offset` characters ahead
+    pub fn declared_at(&self, tokenizer: &Tokenizer, offset: usize) -> bool {
+        self.declared.borrow().iter().any(|o| {
+            o.chars().enumerate().all(|(i, c)| tokenizer.peek_n(offset + i) == Some(&c))
+        })
+    }
+
+    pub fn declare(&self, symbol: &str) {
+        let mut operators = self.operators.borrow_mut();
+
+        self.declared.borrow_mut().push(symbol.to_string());
+
+        if !operators.iter().any(|o| o == symbol) {
+            // longest first, so `>>=` isn't lexed as `>` followed by `>=`
+            let at = operators.iter().position(|o| o.len() < symbol.len()).unwrap_or(operators.len());
+            operators.insert(at, symbol.to_string())
+        }
+    }
+}
+
+impl Matcher for DeclarationMatcher {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
+        let mut symbol = String::new();
+        while !tokenizer.end() && OPERATOR_CHARS.contains(*tokenizer.peek().unwrap()) {
+            symbol.push(tokenizer.next().unwrap());
+        }
+        if symbol.is_empty() {
+            return Ok(None)
+        }
+
+        // left to the parser to reject, uses of it being comments
+        if !symbol.starts_with("--") {
+            self.declare(&symbol)
+        }
+
+        token!(tokenizer, Operator, symbol)
     }
 }
 
@@ -245,16 +349,17 @@ impl KeyMatcher {
 }
 
 impl Matcher for KeyMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         for constant in self.constants.clone() {
             let dat = tokenizer.clone().take(constant.len());
             if dat.size_hint().1.unwrap() != constant.len() {
-                return None
+                continue
             }
             if dat.collect::<String>() == constant {
+                // part of a longer identifier
                 if let Some(c) = tokenizer.peek_n(constant.len()) {
-                    if "_?".contains(*c) || c.is_alphanumeric() {
-                        return None
+                    if "_?'".contains(*c) || c.is_alphanumeric() {
+                        continue
                     }
                 }
 
@@ -262,14 +367,14 @@ impl Matcher for KeyMatcher {
                 return token!(tokenizer, self.token_type.clone(), constant)
             }
         }
-        None
+        Ok(None)
     }
 }
 
 pub struct IdentifierMatcher;
 
 impl Matcher for IdentifierMatcher {
-    fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
+    fn try_match(&self, tokenizer: &mut Tokenizer) -> LexResult<Option<Token>> {
         let mut identifier = String::new();
         while !tokenizer.end() {
             let current = *tokenizer.peek().unwrap();
@@ -282,7 +387,7 @@ impl Matcher for IdentifierMatcher {
         if !identifier.is_empty() {
             token!(tokenizer, Identifier, identifier)
         } else {
-            None
+            Ok(None)
         }
     }
 }
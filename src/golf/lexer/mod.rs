@@ -1,9 +1,15 @@
+mod error;
 pub mod token;
 pub mod tokenizer;
 pub mod matcher;
 pub mod lexer;
 
+pub use super::*;
+
+pub use self::error::*;
 pub use self::token::*;
 pub use self::tokenizer::*;
 pub use self::matcher::*;
 pub use self::lexer::*;
+
+pub type LexResult<T> = Result<T, Diagnostic>;
@@ -11,6 +11,8 @@ pub enum TokenType {
     Operator,
     Identifier,
     Whitespace,
+    Comment,
+    DocComment,
     Indent,
     EOL,
     EOF,
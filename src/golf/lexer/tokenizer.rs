@@ -106,23 +106,34 @@ impl Tokenizer {
         self.peek_snapshot().unwrap().pos
     }
 
-    pub fn try_match_token(&mut self, matcher: &Matcher) -> Option<Token> {
+    // from where the token being matched started, up to the current position
+    pub fn span(&self) -> Span {
+        Span::new(self.last_position(), self.pos)
+    }
+
+    pub fn try_match_token(&mut self, matcher: &Matcher) -> LexResult<Option<Token>> {
         if self.end() {
-            return Some(Token::new(TokenType::EOF,
-                                   TokenPosition::new(self.index, self.index),
-                                   String::new()));
+            return Ok(Some(Token::new(TokenType::EOF,
+                                      TokenPosition::new(self.index, self.index),
+                                      String::new())));
         }
 
         self.take_snapshot();
         match matcher.try_match(self) {
-            Some(t) => {
+            Ok(Some(t)) => {
                 self.commit_snapshot();
-                Some(t)
+                Ok(Some(t))
             }
 
-            None => {
+            Ok(None) => {
                 self.rollback_snapshot();
-                None
+                Ok(None)
+            }
+
+            // the bad input is consumed, so lexing can carry on after it
+            Err(err) => {
+                self.commit_snapshot();
+                Err(err)
             }
         }
     }
@@ -0,0 +1,7 @@
+pub mod symbols;
+pub mod server;
+
+pub use super::*;
+
+pub use self::symbols::*;
+pub use self::server::*;
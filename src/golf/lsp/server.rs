@@ -0,0 +1,413 @@
+use super::*;
+
+use serde_json::Value;
+
+use std::io;
+use std::io::prelude::*;
+use std::collections::HashMap;
+
+const PARSE_ERROR:      i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+struct Document {
+    text:    String,
+    // the last version that parsed, kept through edits that don't so lookups away from them still work
+    parsed:  String,
+    symbols: Option<Symbols>,
+}
+
+// the lines an edit left alone, the same at the start and at the end of both versions of a text
+#[derive(Debug, Copy, Clone)]
+struct Unchanged {
+    before: usize,
+    after:  usize,
+    old:    usize,
+    new:    usize,
+}
+
+impl Unchanged {
+    fn new(old: &str, new: &str) -> Unchanged {
+        let old: Vec<&str> = old.split('\n').collect();
+        let new: Vec<&str> = new.split('\n').collect();
+
+        let before = old.iter().zip(&new).take_while(|&(a, b)| a == b).count();
+        let after  = old.iter().rev().zip(new.iter().rev()).take(old.len().min(new.len()) - before).take_while(|&(a, b)| a == b).count();
+
+        Unchanged {
+            before, after,
+            old: old.len(),
+            new: new.len(),
+        }
+    }
+
+    // where 1-based `line` of a version with `from` lines is in the one with `to` lines, `None` in the edit
+    fn moved(&self, line: usize, from: usize, to: usize) -> Option<usize> {
+        if line <= self.before {
+            Some(line)
+        } else if line > from - self.after {
+            Some(line + to - from)
+        } else {
+            None
+        }
+    }
+
+    fn in_old(&self, position: TokenPosition) -> Option<TokenPosition> {
+        self.moved(position.line, self.new, self.old).map(|line| TokenPosition::new(line, position.col))
+    }
+
+    fn in_new(&self, span: &Span) -> Option<Span> {
+        let start = self.moved(span.start.line, self.old, self.new)?;
+        let end   = self.moved(span.end.line, self.old, self.new)?;
+
+        Some(Span::new(TokenPosition::new(start, span.start.col), TokenPosition::new(end, span.end.col)))
+    }
+}
+
+// the current text, and its ranges for spans in the version the symbols are from
+struct View<'a> {
+    text:      &'a str,
+    unchanged: Unchanged,
+}
+
+impl<'a> View<'a> {
+    fn range(&self, span: &Span) -> Option<Value> {
+        self.unchanged.in_new(span).map(|span| range(self.text, &span))
+    }
+}
+
+pub struct Server<W: Write> {
+    output:    W,
+    documents: HashMap<String, Document>,
+    shutdown:  bool,
+}
+
+// answers messages from `input` until told to exit, returning the exit code the protocol asks for
+pub fn serve<R: BufRead, W: Write>(mut input: R, output: W) -> io::Result<i32> {
+    let mut server = Server::new(output);
+
+    while let Some(frame) = read(&mut input)? {
+        // one bad message is skipped, rather than taking the session down with it
+        let message = match frame {
+            Frame::Message(message)    => message,
+            Frame::Invalid(reason, id) => {
+                eprintln!("golf lsp: skipping a message, {}", reason);
+
+                if !id.is_null() {
+                    server.send(json!({
+                        "jsonrpc": "2.0",
+                        "id":      id,
+                        "error":   { "code": PARSE_ERROR, "message": reason },
+                    }))?
+                }
+
+                continue
+            },
+        };
+
+        if let Some(code) = server.handle(&message)? {
+            return Ok(code)
+        }
+    }
+
+    Ok(if server.shutdown { 0 } else { 1 })
+}
+
+// a message, or why it couldn't be read with its id when that can still be made out
+enum Frame {
+    Message(Value),
+    Invalid(String, Value),
+}
+
+// one `Content-Length` framed message, `None` once the input is closed
+fn read<R: BufRead>(input: &mut R) -> io::Result<Option<Frame>> {
+    let mut length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None)
+        }
+
+        let line = line.trim();
+
+        if line.is_empty() {
+            break
+        }
+
+        // anywhere in the line, a body sent without a length running into the next header
+        if let Some(at) = line.find("Content-Length:") {
+            length = line[at + 15 ..].trim().parse::<usize>().ok()
+        }
+    }
+
+    let length = match length {
+        Some(length) => length,
+        None         => return Ok(Some(Frame::Invalid("no Content-Length".to_string(), Value::Null))),
+    };
+
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(match serde_json::from_slice(&body) {
+        Ok(message) => Frame::Message(message),
+        Err(err)    => Frame::Invalid(format!("invalid json: {}", err), recover_id(&String::from_utf8_lossy(&body))),
+    }))
+}
+
+// the `"id": ..` of a body that isn't valid json, if it's written plainly enough to find
+fn recover_id(body: &str) -> Value {
+    let rest = match body.find("\"id\"") {
+        Some(at) => body[at + 4 ..].trim_start(),
+        None     => return Value::Null,
+    };
+
+    let rest = match rest.strip_prefix(':') {
+        Some(rest) => rest.trim_start(),
+        None       => return Value::Null,
+    };
+
+    let end = rest.find(|c: char| c == ',' || c == '}' || c.is_whitespace()).unwrap_or(rest.len());
+
+    serde_json::from_str(&rest[.. end]).unwrap_or(Value::Null)
+}
+
+// lsp positions count utf-16 units from 0, golf's count characters from line 1
+fn position(text: &str, value: &Value) -> TokenPosition {
+    let line      = value["line"].as_u64().unwrap_or(0) as usize;
+    let character = value["character"].as_u64().unwrap_or(0) as usize;
+
+    let mut units = 0;
+    let mut col   = 0;
+
+    for c in text.lines().nth(line).unwrap_or("").chars() {
+        if units >= character {
+            break
+        }
+
+        units += c.len_utf16();
+        col   += 1;
+    }
+
+    TokenPosition::new(line + 1, col)
+}
+
+fn range(text: &str, span: &Span) -> Value {
+    let point = |position: &TokenPosition| {
+        let line = text.lines().nth(position.line.saturating_sub(1)).unwrap_or("");
+        let character: usize = line.chars().take(position.col).map(|c| c.len_utf16()).sum();
+
+        json!({ "line": position.line.saturating_sub(1), "character": character })
+    };
+
+    json!({ "start": point(&span.start), "end": point(&span.end) })
+}
+
+// the parser's and checker's diagnostics, against the prelude `golf run` has
+fn diagnose(text: &str) -> (Diagnostics, Option<Symbols>) {
+    let root = match concrete(text) {
+        Ok(root)         => root,
+        Err(diagnostics) => return (diagnostics, None),
+    };
+
+    let ast = match lower(&root) {
+        Ok(ast)          => ast,
+        Err(diagnostics) => return (diagnostics, None),
+    };
+
+    let checker = Checker::new(ast.clone());
+    let checked = checker.check(&mut prelude());
+
+    let mut diagnostics = checker.warnings();
+
+    if let Err(err) = checked {
+        diagnostics.push(err)
+    }
+
+    (diagnostics, Some(Symbols::new(&ast, &root)))
+}
+
+impl<W: Write> Server<W> {
+    pub fn new(output: W) -> Server<W> {
+        Server {
+            output,
+            documents: HashMap::new(),
+            shutdown:  false,
+        }
+    }
+
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        let body = message.to_string();
+
+        write!(self.output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.output.flush()
+    }
+
+    fn respond(&mut self, id: &Value, result: Value) -> io::Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    // `Some` with the exit code once the client sends `exit`
+    pub fn handle(&mut self, message: &Value) -> io::Result<Option<i32>> {
+        let id     = &message["id"];
+        let params = &message["params"];
+        let uri    = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+
+        match message["method"].as_str().unwrap_or("") {
+            "initialize" => self.respond(id, json!({
+                "capabilities": {
+                    "textDocumentSync":   1,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "hoverProvider":      true,
+                    "completionProvider": {},
+                },
+                "serverInfo": { "name": "golf" },
+            }))?,
+
+            "shutdown" => {
+                self.shutdown = true;
+                self.respond(id, Value::Null)?
+            },
+
+            "exit" => return Ok(Some(if self.shutdown { 0 } else { 1 })),
+
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                self.update(&uri, text)?
+            },
+
+            // only whole documents are synced, so the last change is the new text
+            "textDocument/didChange" => {
+                let text = params["contentChanges"].as_array()
+                                                   .and_then(|changes| changes.last())
+                                                   .and_then(|change| change["text"].as_str());
+
+                if let Some(text) = text {
+                    self.update(&uri, text.to_string())?
+                }
+            },
+
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] }))?
+            },
+
+            "textDocument/definition" => {
+                let result = self.lookup(&uri, params, |view, symbols, at| {
+                    symbols.definition(at).and_then(|d| view.range(&d.span)).map(|range| json!({ "uri": uri, "range": range }))
+                });
+
+                self.respond(id, result.unwrap_or(Value::Null))?
+            },
+
+            "textDocument/references" => {
+                let declaration = params["context"]["includeDeclaration"].as_bool().unwrap_or(true);
+
+                let result = self.lookup(&uri, params, |view, symbols, at| {
+                    let locations: Vec<Value> = symbols.references(at, declaration).iter().filter_map(|span| {
+                        view.range(span).map(|range| json!({ "uri": uri, "range": range }))
+                    }).collect();
+
+                    Some(json!(locations))
+                });
+
+                self.respond(id, result.unwrap_or_else(|| json!([])))?
+            },
+
+            "textDocument/hover" => {
+                let result = self.lookup(&uri, params, |view, symbols, at| {
+                    let reference = symbols.reference(at)?;
+                    let hover     = &symbols.definitions[reference.definition?].hover;
+
+                    Some(json!({
+                        "contents": { "kind": "markdown", "value": hover },
+                        "range":    view.range(&reference.span)?,
+                    }))
+                });
+
+                self.respond(id, result.unwrap_or(Value::Null))?
+            },
+
+            "textDocument/completion" => {
+                let result = self.lookup(&uri, params, |_, symbols, at| {
+                    let items: Vec<Value> = symbols.completions(at).iter().map(|name| {
+                        json!({ "label": name, "kind": 6 })
+                    }).collect();
+
+                    Some(json!(items))
+                });
+
+                self.respond(id, result.unwrap_or_else(|| json!([])))?
+            },
+
+            method => if !id.is_null() {
+                self.send(json!({
+                    "jsonrpc": "2.0",
+                    "id":      id,
+                    "error":   { "code": METHOD_NOT_FOUND, "message": format!("unsupported method: {}", method) },
+                }))?
+            },
+        }
+
+        Ok(None)
+    }
+
+    fn lookup<F>(&self, uri: &str, params: &Value, f: F) -> Option<Value> where F: Fn(&View, &Symbols, TokenPosition) -> Option<Value> {
+        let document = self.documents.get(uri)?;
+        let symbols  = document.symbols.as_ref()?;
+
+        let view = View {
+            text:      &document.text,
+            unchanged: Unchanged::new(&document.parsed, &document.text),
+        };
+
+        let at = view.unchanged.in_old(position(&document.text, &params["position"]))?;
+
+        f(&view, symbols, at)
+    }
+
+    fn update(&mut self, uri: &str, text: String) -> io::Result<()> {
+        let (diagnostics, symbols) = diagnose(&text);
+
+        let (parsed, symbols) = match (symbols, self.documents.remove(uri)) {
+            (Some(symbols), _)    => (text.clone(), Some(symbols)),
+            (None, Some(before))  => (before.parsed, before.symbols),
+            (None, None)          => (text.clone(), None),
+        };
+
+        let published: Vec<Value> = diagnostics.iter().map(|d| {
+            let related: Vec<Value> = d.labels.iter().map(|label| json!({
+                "location": { "uri": uri, "range": range(&text, &label.span) },
+                "message":  label.message,
+            })).collect();
+
+            // one about the whole file, like an i/o error, goes on its first line
+            let span = d.span.unwrap_or_else(|| {
+                let first = text.lines().next().unwrap_or("").chars().count();
+                Span::new(TokenPosition::new(1, 0), TokenPosition::new(1, first))
+            });
+
+            json!({
+                "range":              range(&text, &span),
+                "severity":           match d.severity {
+                    Severity::Error   => 1,
+                    Severity::Warning => 2,
+                    Severity::Note    => 3,
+                },
+                "code":               d.code.id,
+                "source":             "golf",
+                "message":            d.message,
+                "relatedInformation": related,
+            })
+        }).collect();
+
+        self.documents.insert(uri.to_string(), Document { text, parsed, symbols });
+
+        self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": published }))
+    }
+}
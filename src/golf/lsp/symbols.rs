@@ -0,0 +1,303 @@
+use super::*;
+
+use std::rc::Rc;
+
+// a binding, with what hovering over it shows
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name:  String,
+    pub span:  Span,
+    pub hover: String,
+}
+
+// a name as it's written, and the binding it refers to, `None` for the prelude's
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub span:       Span,
+    pub definition: Option<usize>,
+}
+
+// the body of an arm, and the names it can see
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub start: TokenPosition,
+    pub end:   TokenPosition,
+    pub names: Vec<String>,
+}
+
+// what every name in a file refers to, resolved through the same `SymTab` scopes the checker uses
+#[derive(Debug, Clone)]
+pub struct Symbols {
+    pub definitions: Vec<Definition>,
+    pub references:  Vec<Reference>,
+    pub scopes:      Vec<Scope>,
+    pub globals:     Vec<String>,
+}
+
+fn contains(span: &Span, position: TokenPosition) -> bool {
+    let at = (position.line, position.col);
+
+    (span.start.line, span.start.col) <= at && at <= (span.end.line, span.end.col)
+}
+
+impl Symbols {
+    // `root` is the concrete tree `ast` was lowered from, where functions end
+    pub fn new(ast: &[Statement], root: &SyntaxTree) -> Symbols {
+        let mut resolver = Resolver {
+            symbols: Symbols {
+                definitions: Vec::new(),
+                references:  Vec::new(),
+                scopes:      Vec::new(),
+                globals:     Vec::new(),
+            },
+            frames:  vec![Vec::new()],
+            braces:  braces(root),
+        };
+
+        let mut sym = prelude();
+
+        for statement in ast {
+            resolver.statement(&mut sym, statement)
+        }
+
+        resolver.symbols.globals = sym.visible();
+
+        resolver.symbols
+    }
+
+    pub fn reference(&self, position: TokenPosition) -> Option<&Reference> {
+        self.references.iter().find(|r| contains(&r.span, position))
+    }
+
+    pub fn definition(&self, position: TokenPosition) -> Option<&Definition> {
+        self.reference(position).and_then(|r| r.definition).map(|d| &self.definitions[d])
+    }
+
+    // every use of the binding at `position`, and the binding itself with `declaration`
+    pub fn references(&self, position: TokenPosition, declaration: bool) -> Vec<Span> {
+        let definition = match self.reference(position).and_then(|r| r.definition) {
+            Some(definition) => definition,
+            None             => return Vec::new(),
+        };
+
+        let site = self.definitions[definition].span;
+
+        self.references.iter()
+                       .filter(|r| r.definition == Some(definition))
+                       .filter(|r| declaration || r.span != site)
+                       .map(|r| r.span)
+                       .collect()
+    }
+
+    // the names in scope at `position`, the innermost arm's when it's inside one
+    pub fn completions(&self, position: TokenPosition) -> Vec<String> {
+        let at = (position.line, position.col);
+
+        let innermost = self.scopes.iter()
+                                   .filter(|s| (s.start.line, s.start.col) <= at && at <= (s.end.line, s.end.col))
+                                   .max_by_key(|s| (s.start.line, s.start.col));
+
+        let mut names = match innermost {
+            Some(scope) => scope.names.clone(),
+            None        => self.globals.clone(),
+        };
+
+        names.sort();
+        names
+    }
+}
+
+// where each `{` is closed
+fn braces(root: &SyntaxTree) -> Vec<(TokenPosition, TokenPosition)> {
+    let mut open   = Vec::new();
+    let mut braces = Vec::new();
+
+    for token in &root.tokens {
+        if token.token.token_type != TokenType::Symbol {
+            continue
+        }
+
+        match token.token.content().as_str() {
+            "{" => open.push(token.token.position),
+            "}" => if let Some(start) = open.pop() {
+                braces.push((start, token.token.position))
+            },
+            _   => (),
+        }
+    }
+
+    braces
+}
+
+struct Resolver {
+    symbols: Symbols,
+    // the definitions in each scope of the symtab chain by their symtab index, innermost last
+    frames:  Vec<Vec<Option<usize>>>,
+    braces:  Vec<(TokenPosition, TokenPosition)>,
+}
+
+impl Resolver {
+    fn define(&mut self, sym: &SymTab, name: &str, position: TokenPosition, hover: String) {
+        let slot       = sym.add_name(name);
+        let definition = self.symbols.definitions.len();
+        let span       = Span::width(position, name.chars().count());
+
+        let frame = self.frames.last_mut().unwrap();
+
+        if frame.len() <= slot {
+            frame.resize(slot + 1, None)
+        }
+
+        frame[slot] = Some(definition);
+
+        self.symbols.definitions.push(Definition { name: name.to_string(), span, hover });
+        self.symbols.references.push(Reference { span, definition: Some(definition) })
+    }
+
+    fn resolve(&mut self, sym: &SymTab, name: &str, position: TokenPosition) {
+        let definition = match sym.get_name(name) {
+            Some((slot, up)) => {
+                let frame = &self.frames[self.frames.len() - 1 - up];
+                frame.get(slot).cloned().and_then(|d| d)
+            },
+            None             => return,
+        };
+
+        self.symbols.references.push(Reference { span: Span::width(position, name.chars().count()), definition })
+    }
+
+    fn statement(&mut self, sym: &mut SymTab, statement: &Statement) {
+        match *statement {
+            Statement::Expression(ref expression) => self.expression(sym, expression),
+            Statement::Assignment(ref assignment) => if let Expression::Identifier(ref name, position) = *assignment.left {
+                let hover = hover(name, &assignment.right, &assignment.doc);
+
+                self.define(sym, name, position, hover);
+                self.expression(sym, &assignment.right)
+            },
+            Statement::Fixity(_)                  => (),
+        }
+    }
+
+    fn expression(&mut self, sym: &mut SymTab, expression: &Expression) {
+        match *expression {
+            Expression::Block(ref statements) => for statement in statements {
+                self.statement(sym, statement)
+            },
+
+            Expression::Identifier(ref name, position) => self.resolve(sym, name, position),
+
+            Expression::Operation(ref operation) => {
+                self.expression(sym, &operation.left);
+                self.expression(sym, &operation.right)
+            },
+
+            Expression::Unary(ref unary) => self.expression(sym, &unary.operand),
+
+            Expression::Call(ref call) => {
+                self.expression(sym, &call.callee);
+
+                for arg in &call.args {
+                    self.expression(sym, arg)
+                }
+            },
+
+            Expression::Index(ref index) => {
+                self.expression(sym, &index.id);
+                self.expression(sym, &index.index)
+            },
+
+            Expression::Function(ref function) => self.function(sym, function),
+
+            _ => (),
+        }
+    }
+
+    fn function(&mut self, sym: &mut SymTab, function: &Function) {
+        let statements = match *function.arms {
+            Expression::Block(ref statements) => statements,
+            _                                 => return,
+        };
+
+        let end = self.braces.iter()
+                             .find(|b| b.0 == function.position)
+                             .map(|b| b.1)
+                             .unwrap_or_else(|| TokenPosition::new(usize::MAX, 0));
+
+        let arms: Vec<&Arm> = statements.iter().filter_map(arm).collect();
+
+        for statement in statements {
+            let arm = match arm(statement) {
+                Some(arm) => arm,
+                None      => {
+                    self.statement(sym, statement);
+                    continue
+                },
+            };
+
+            let mut names = Vec::new();
+
+            for param in &arm.params {
+                if let Expression::Identifier(ref name, _) = **param {
+                    names.push(name.clone())
+                }
+            }
+
+            let mut local = SymTab::new(Rc::new(sym.clone()), &names);
+
+            self.frames.push(Vec::new());
+
+            for param in &arm.params {
+                if let Expression::Identifier(ref name, position) = **param {
+                    let hover = format!("```golf\n{}\n```\nparameter of `{}`", name, doc::pattern(arm));
+                    self.define(&local, name, position, hover)
+                }
+            }
+
+            self.statement(&mut local, &arm.body);
+
+            self.frames.pop();
+
+            // an arm's body runs up to the next arm, or the end of the function
+            let next = arms.iter().position(|a| a.position == arm.position).and_then(|i| arms.get(i + 1));
+
+            self.symbols.scopes.push(Scope {
+                start: arm.position,
+                end:   next.map(|a| a.position).unwrap_or(end),
+                names: local.visible(),
+            })
+        }
+    }
+}
+
+fn arm(statement: &Statement) -> Option<&Arm> {
+    match *statement {
+        Statement::Expression(ref expression) => match **expression {
+            Expression::Arm(ref arm) => Some(arm),
+            _                        => None,
+        },
+        _ => None,
+    }
+}
+
+// the binding as markdown, with its arms when it's a function and its doc comment
+fn hover(name: &str, value: &Expression, doc: &Option<Rc<String>>) -> String {
+    let mut code = name.to_string();
+
+    if let Expression::Function(ref function) = *value {
+        code.push_str(" = {\n");
+
+        if let Expression::Block(ref statements) = *function.arms {
+            for arm in statements.iter().filter_map(arm) {
+                code.push_str(&format!("  {}\n", doc::pattern(arm)))
+            }
+        }
+
+        code.push('}')
+    }
+
+    match *doc {
+        Some(ref doc) => format!("```golf\n{}\n```\n{}", code, doc),
+        None          => format!("```golf\n{}\n```", code),
+    }
+}
@@ -0,0 +1,181 @@
+use super::*;
+
+use toml::{Table, Value};
+
+use std::path::PathBuf;
+
+// a project's `golf.toml`, with paths relative to the directory it's in
+//
+// [project]
+// name    = "game"
+// roots   = ["src"]
+// entries = ["main"]
+// out-dir = "build"
+// target  = "5.4"
+//
+// [profile.release]
+// match-errors = false
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub name:     String,
+    // directories holding the modules, a module being named by its path in one without `.golf`
+    pub roots:    Vec<PathBuf>,
+    // the modules a host loads first, every other one is built too
+    pub entries:  Vec<String>,
+    pub out_dir:  PathBuf,
+    pub target:   Target,
+    // `debug` and `release`, as the manifest sets them, and any others it declares
+    pub profiles: Vec<(String, Options)>,
+}
+
+// the line where `key` is set under the `[section]` header, or before any header when `section` is empty,
+// to point at when its value is wrong
+fn locate(source: &str, section: &str, key: &str) -> Option<Span> {
+    let mut current = "";
+
+    for (i, line) in source.lines().enumerate() {
+        let col  = line.len() - line.trim_start().len();
+        let line = line.trim_start();
+
+        if line.starts_with('[') {
+            current = line.trim_start_matches('[').split(']').next().unwrap_or("").trim();
+
+            // a section that shouldn't be there is pointed at by its header
+            if section.is_empty() && current.split('.').next() == Some(key) {
+                return Some(Span::width(TokenPosition::new(i + 1, line.find(key).unwrap_or(0) + col), key.len()))
+            }
+
+            continue
+        }
+
+        if current == section && line.starts_with(key) && line[key.len() ..].trim_start().starts_with('=') {
+            return Some(Span::width(TokenPosition::new(i + 1, col), key.len()))
+        }
+    }
+
+    None
+}
+
+fn invalid(source: &str, section: &str, key: &str, message: &str) -> Diagnostic {
+    match locate(source, section, key) {
+        Some(span) => Diagnostic::error(INVALID_MANIFEST, span, message),
+        None       => Diagnostic::new(Severity::Error, INVALID_MANIFEST, message),
+    }
+}
+
+impl Manifest {
+    pub fn parse(source: &str) -> Result<Manifest, Diagnostic> {
+        let table = source.parse::<Table>().map_err(|err| {
+            let message = format!("invalid golf.toml: {}", err.message());
+
+            match err.span() {
+                Some(range) => {
+                    let before = &source[.. range.start];
+                    let line   = before.matches('\n').count() + 1;
+                    let col    = before.chars().rev().take_while(|c| *c != '\n').count();
+
+                    Diagnostic::error(INVALID_MANIFEST, Span::width(TokenPosition::new(line, col), 1), &message)
+                },
+                None        => Diagnostic::new(Severity::Error, INVALID_MANIFEST, &message),
+            }
+        })?;
+
+        let mut manifest = Manifest {
+            name:     String::new(),
+            roots:    vec![PathBuf::from("src")],
+            entries:  Vec::new(),
+            out_dir:  PathBuf::from("build"),
+            target:   Target::default(),
+            profiles: vec![
+                ("debug".to_string(),   Options { match_errors: true,  .. Options::default() }),
+                ("release".to_string(), Options { match_errors: false, .. Options::default() }),
+            ],
+        };
+
+        for (section, value) in &table {
+            match (section.as_str(), value.as_table()) {
+                ("project", Some(project))  => manifest.project(source, project)?,
+                ("profile", Some(profiles)) => {
+                    // `debug` goes first, since the others start from it
+                    let mut profiles: Vec<(&String, &Value)> = profiles.iter().collect();
+                    profiles.sort_by_key(|p| p.0 != "debug");
+
+                    for (name, profile) in profiles {
+                        match profile.as_table() {
+                            Some(profile) => manifest.profile(source, name, profile)?,
+                            None          => return Err(invalid(source, "profile", name, &format!("`profile.{}` must be a table", name))),
+                        }
+                    }
+                },
+                _                           => return Err(invalid(source, "", section, &format!("unknown section `{}`, expected `project` or `profile`", section))),
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    fn project(&mut self, source: &str, project: &Table) -> Result<(), Diagnostic> {
+        let strings = |key: &str, value: &Value| -> Result<Vec<String>, Diagnostic> {
+            let strings = value.as_array().and_then(|array| array.iter().map(|v| v.as_str().map(|s| s.to_string())).collect());
+            strings.ok_or_else(|| invalid(source, "project", key, &format!("`{}` must be a list of strings", key)))
+        };
+
+        let string = |key: &str, value: &Value| -> Result<String, Diagnostic> {
+            value.as_str().map(|s| s.to_string()).ok_or_else(|| invalid(source, "project", key, &format!("`{}` must be a string", key)))
+        };
+
+        for (key, value) in project {
+            match key.as_str() {
+                "name"    => self.name = string(key, value)?,
+                "roots"   => self.roots = strings(key, value)?.into_iter().map(PathBuf::from).collect(),
+                "entries" => self.entries = strings(key, value)?,
+                "out-dir" => self.out_dir = PathBuf::from(string(key, value)?),
+                "target"  => {
+                    let target = string(key, value)?;
+
+                    self.target = match Target::from_name(&target) {
+                        Some(target) => target,
+                        None         => {
+                            let names: Vec<&str> = Target::ALL.iter().map(|t| t.name()).collect();
+                            return Err(invalid(source, "project", key, &format!("unknown target `{}`, expected one of {}", target, names.join(", "))))
+                        },
+                    }
+                },
+                _         => return Err(invalid(source, "project", key, &format!("unknown key `{}` in `project`", key))),
+            }
+        }
+
+        Ok(())
+    }
+
+    // a profile starts from `debug`'s options, or the ones it overrides
+    fn profile(&mut self, source: &str, name: &str, profile: &Table) -> Result<(), Diagnostic> {
+        let mut options = self.options(name).or_else(|| self.options("debug")).unwrap();
+        let section     = format!("profile.{}", name);
+
+        for (key, value) in profile {
+            let flag = match value.as_bool() {
+                Some(flag) => flag,
+                None       => return Err(invalid(source, &section, key, &format!("`{}` must be true or false", key))),
+            };
+
+            match key.as_str() {
+                "prelude"      => options.prelude = flag,
+                "global"       => options.global = flag,
+                "match-errors" => options.match_errors = flag,
+                _              => return Err(invalid(source, &section, key, &format!("unknown option `{}` in `profile.{}`", key, name))),
+            }
+        }
+
+        match self.profiles.iter_mut().find(|p| p.0 == name) {
+            Some(existing) => existing.1 = options,
+            None           => self.profiles.push((name.to_string(), options)),
+        }
+
+        Ok(())
+    }
+
+    pub fn options(&self, profile: &str) -> Option<Options> {
+        self.profiles.iter().find(|p| p.0 == profile).map(|p| p.1.clone())
+    }
+}
@@ -1,9 +1,31 @@
+extern crate mlua;
+extern crate toml;
+#[macro_use]
+extern crate serde_json;
+
+pub mod diagnostic;
+mod error;
 pub mod lexer;
 pub mod parser;
+pub mod syntax;
 pub mod checker;
 pub mod transpiler;
+pub mod runtime;
+pub mod compile;
+pub mod manifest;
+// not glob exported, its names are generic
+pub mod doc;
+pub mod format;
+pub mod lsp;
 
+// each phase keeps its diagnostic codes in a private `error` module, so these globs don't clash
+pub use self::diagnostic::*;
+pub use self::error::*;
 pub use self::lexer::*;
 pub use self::parser::*;
+pub use self::syntax::*;
 pub use self::checker::*;
 pub use self::transpiler::*;
+pub use self::runtime::*;
+pub use self::compile::*;
+pub use self::manifest::*;
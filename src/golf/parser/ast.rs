@@ -5,12 +5,15 @@ use super::*;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Block(Vec<Statement>),
-    Number(f64),
+    Number(i64),
+    // written with a fraction or exponent, kept apart since lua 5.3 has integers too
+    Float(f64),
     Bool(bool),
     Str(Rc<String>),
     Char(char),
     Identifier(Rc<String>, TokenPosition),
     Operation(Operation),
+    Unary(Unary),
     Call(Call),
     Index(Index),
     Function(Function),
@@ -27,6 +30,13 @@ pub struct Operation {
     pub position: TokenPosition,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unary {
+    pub op:       UnaryOperand,
+    pub operand:  Rc<Expression>,
+    pub position: TokenPosition,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Call {
     pub callee:   Rc<Expression>,
@@ -52,71 +62,222 @@ pub struct Arm {
     pub params:   Vec<Rc<Expression>>,
     pub body:     Rc<Statement>,
     pub position: TokenPosition,
+    // from the `---` comment lines right above it
+    pub doc:      Option<Rc<String>>,
+}
+
+impl Arm {
+    // the operator the arm overloads, when its first parameter is one
+    pub fn operand(&self) -> Option<&Operand> {
+        match self.params.first() {
+            Some(p) => match **p {
+                Expression::Operand(ref op) => Some(op),
+                _                           => None,
+            },
+            None => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Expression(Rc<Expression>),
     Assignment(Assignment),
+    Fixity(Fixity),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assignment {
-    pub left:  Rc<Expression>,
-    pub right: Rc<Expression>,
+    pub left:     Rc<Expression>,
+    pub right:    Rc<Expression>,
     pub position: TokenPosition,
+    pub doc:      Option<Rc<String>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operand {
     Pow,
-    Mul, Div, Mod,
+    Mul, Div, IntDiv, Mod,
     Add, Sub,
+    BAnd, BOr, BXor, Shl, Shr,
     Equal, NEqual,
     Lt, Gt, LtEqual, GtEqual,
     Concat, Combine,
+    And, Or,
     PipeLeft, PipeRight,
+    // only ever the first parameter of an arm, overloading `a[k]`
+    Index,
+    // declared by the program itself
+    Infix(Fixity),
+}
+
+// `infixl 4 <$>`, an operator declared by the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixity {
+    pub symbol:     Rc<String>,
+    pub precedence: u8,
+    pub assoc:      Assoc,
+    pub position:   TokenPosition,
+}
+
+// prefix operators, binding tighter than everything but `^` and `.`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UnaryOperand {
+    Neg,
+    Not,
+    BNot,
+}
+
+impl UnaryOperand {
+    pub fn from_str(v: &str) -> Option<UnaryOperand> {
+        match v {
+            "-"    => Some(UnaryOperand::Neg),
+            "not"  => Some(UnaryOperand::Not),
+            "bnot" => Some(UnaryOperand::BNot),
+            _      => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            UnaryOperand::Neg  => "-",
+            UnaryOperand::Not  => "not",
+            UnaryOperand::BNot => "bnot",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Assoc {
+    Left,
+    Right,
 }
 
 impl Operand {
-    pub fn from_str(v: &str) -> Option<(Operand, u8)> {
+    pub fn from_str(v: &str) -> Option<Operand> {
         match v {
-            "^"   => Some((Operand::Pow, 0)),
-            "*"   => Some((Operand::Mul, 1)),
-            "/"   => Some((Operand::Div, 1)),
-            "%"   => Some((Operand::Mod, 1)),
-            "+"   => Some((Operand::Add, 2)),
-            "-"   => Some((Operand::Sub, 2)),
-            "=="  => Some((Operand::Equal, 3)),
-            "~="  => Some((Operand::NEqual, 3)),
-            "<"   => Some((Operand::Lt, 4)),
-            ">"   => Some((Operand::Gt, 4)),
-            "<="  => Some((Operand::LtEqual, 4)),
-            ">="  => Some((Operand::GtEqual, 4)),
-            "."   => Some((Operand::Combine, 5)),
-            "++"  => Some((Operand::Concat, 5)),
-            "<|"  => Some((Operand::PipeLeft, 5)),
-            "|>"  => Some((Operand::PipeRight, 5)),
-            _     => None,
+            "^"   => Some(Operand::Pow),
+            "*"   => Some(Operand::Mul),
+            "/"   => Some(Operand::Div),
+            "//"  => Some(Operand::IntDiv),
+            "%"   => Some(Operand::Mod),
+            "+"   => Some(Operand::Add),
+            "-"   => Some(Operand::Sub),
+            "=="  => Some(Operand::Equal),
+            "~="  => Some(Operand::NEqual),
+            "<"   => Some(Operand::Lt),
+            ">"   => Some(Operand::Gt),
+            "<="  => Some(Operand::LtEqual),
+            ">="  => Some(Operand::GtEqual),
+            "."   => Some(Operand::Combine),
+            "++"  => Some(Operand::Concat),
+            "<|"  => Some(Operand::PipeLeft),
+            "|>"  => Some(Operand::PipeRight),
+            "and" => Some(Operand::And),
+            "or"  => Some(Operand::Or),
+
+            "band" => Some(Operand::BAnd),
+            "bor"  => Some(Operand::BOr),
+            "bxor" => Some(Operand::BXor),
+            "shl"  => Some(Operand::Shl),
+            "shr"  => Some(Operand::Shr),
+            _      => None,
         }
     }
-    
-    pub fn to_string(&self) -> String {
+
+    // how tightly the operator binds, higher first, and which way a chain of it groups
+    pub fn binding(&self) -> (u8, Assoc) {
+        match *self {
+            Operand::Combine   => (11, Assoc::Right),
+            Operand::Pow       => (10, Assoc::Right),
+            Operand::Mul       |
+            Operand::Div       |
+            Operand::IntDiv    |
+            Operand::Mod       => (9, Assoc::Left),
+            Operand::Add       |
+            Operand::Sub       => (8, Assoc::Left),
+            Operand::Concat    => (7, Assoc::Right),
+            // between `++` and the comparisons as in lua, so `x band 1 == 0` needs no parentheses
+            Operand::BAnd      |
+            Operand::BOr       |
+            Operand::BXor      |
+            Operand::Shl       |
+            Operand::Shr       => (6, Assoc::Left),
+            Operand::Lt        |
+            Operand::Gt        |
+            Operand::LtEqual   |
+            Operand::GtEqual   => (5, Assoc::Left),
+            Operand::Equal     |
+            Operand::NEqual    => (4, Assoc::Left),
+            Operand::And       => (3, Assoc::Left),
+            Operand::Or        => (2, Assoc::Left),
+            Operand::PipeRight => (1, Assoc::Left),
+            Operand::PipeLeft  => (0, Assoc::Right),
+            Operand::Index     => (12, Assoc::Left),
+            Operand::Infix(ref f) => (f.precedence, f.assoc),
+        }
+    }
+
+    // how the operator is written in golf
+    pub fn symbol(&self) -> &str {
+        match *self {
+            Operand::Pow       => "^",
+            Operand::Mul       => "*",
+            Operand::Div       => "/",
+            Operand::IntDiv    => "//",
+            Operand::Mod       => "%",
+            Operand::Add       => "+",
+            Operand::Sub       => "-",
+            Operand::BAnd      => "band",
+            Operand::BOr       => "bor",
+            Operand::BXor      => "bxor",
+            Operand::Shl       => "shl",
+            Operand::Shr       => "shr",
+            Operand::Equal     => "==",
+            Operand::NEqual    => "~=",
+            Operand::Lt        => "<",
+            Operand::Gt        => ">",
+            Operand::LtEqual   => "<=",
+            Operand::GtEqual   => ">=",
+            Operand::Concat    => "++",
+            Operand::Combine   => ".",
+            Operand::And       => "and",
+            Operand::Or        => "or",
+            Operand::PipeLeft  => "<|",
+            Operand::PipeRight => "|>",
+            Operand::Index     => "[]",
+            Operand::Infix(ref f) => &f.symbol,
+        }
+    }
+
+    // the lua metamethod an arm overloading this operator becomes, if lua has one for it
+    pub fn metamethod(&self, unary: bool) -> Option<&'static str> {
+        if unary {
+            return match *self {
+                Operand::Sub => Some("__unm"),
+                _            => None,
+            }
+        }
+
         match *self {
-            Operand::Pow     => "^".to_string(),
-            Operand::Mul     => "*".to_string(),
-            Operand::Div     => "/".to_string(),
-            Operand::Mod     => "%".to_string(),
-            Operand::Add     => "+".to_string(),
-            Operand::Sub     => "-".to_string(),
-            Operand::Equal   => "==".to_string(),
-            Operand::NEqual  => "~=".to_string(),
-            Operand::Lt      => "<".to_string(),
-            Operand::Gt      => ">".to_string(),
-            Operand::LtEqual => "<=".to_string(),
-            Operand::GtEqual => ">=".to_string(),
-            Operand::Combine => "..".to_string(),
-            _                => unreachable!(),
+            Operand::Add     => Some("__add"),
+            Operand::Sub     => Some("__sub"),
+            Operand::Mul     => Some("__mul"),
+            Operand::Div     => Some("__div"),
+            Operand::IntDiv  => Some("__idiv"),
+            Operand::BAnd    => Some("__band"),
+            Operand::BOr     => Some("__bor"),
+            Operand::BXor    => Some("__bxor"),
+            Operand::Shl     => Some("__shl"),
+            Operand::Shr     => Some("__shr"),
+            Operand::Mod     => Some("__mod"),
+            Operand::Pow     => Some("__pow"),
+            Operand::Equal   => Some("__eq"),
+            Operand::Lt      => Some("__lt"),
+            Operand::LtEqual => Some("__le"),
+            Operand::Concat  => Some("__concat"),
+            Operand::Index   => Some("__index"),
+            _                => None,
         }
     }
 }
@@ -1,42 +1,11 @@
 use super::*;
 
-use std::fmt;
-
-#[derive(Debug)]
-pub enum ParserErrorValue {
-    Constant(String),
-}
-
-#[derive(Debug)]
-pub struct ParserError {
-    pub value:    ParserErrorValue,
-    pub position: Option<TokenPosition>,
-}
-
-#[allow(dead_code)]
-impl ParserError {
-    pub fn new(value: &str) -> ParserError {
-        ParserError {
-            value: ParserErrorValue::Constant(value.to_owned()),
-            position: None,
-        }
-    }
-
-    pub fn new_pos(position: TokenPosition, value: &str) -> ParserError {
-        ParserError {
-            value: ParserErrorValue::Constant(value.to_owned()),
-            position: Some(position),
-        }
-    }
-}
-
-impl fmt::Display for ParserError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.value {
-            ParserErrorValue::Constant(ref s) => match self.position {
-                Some(p) => write!(f, "{}: {}", p, s),
-                None    => write!(f, "{}", s),
-            }
-        }
-    }
-}
+// parser diagnostic codes
+
+pub const UNEXPECTED_TOKEN:     Code = Code::new(Phase::Parser, "E0100");
+pub const UNEXPECTED_PARAMETER: Code = Code::new(Phase::Parser, "E0101");
+pub const UNEXPECTED_SYMBOL:    Code = Code::new(Phase::Parser, "E0102");
+pub const EXPECTED_EXPRESSION:  Code = Code::new(Phase::Parser, "E0103");
+pub const EXPECTED_TOKEN:       Code = Code::new(Phase::Parser, "E0104");
+pub const UNEXPECTED_END:       Code = Code::new(Phase::Parser, "E0105");
+pub const INVALID_FIXITY:       Code = Code::new(Phase::Parser, "E0106");
@@ -1,4 +1,4 @@
-pub mod error;
+mod error;
 pub mod traveler;
 pub mod ast;
 pub mod parser;
@@ -10,4 +10,4 @@ pub use self::traveler::*;
 pub use self::ast::*;
 pub use self::parser::*;
 
-pub type ParserResult<T> = Result<T, ParserError>;
+pub type ParserResult<T> = Result<T, Diagnostic>;
@@ -1,27 +1,95 @@
 use std::rc::Rc;
+use std::mem;
 
 use super::*;
 
 pub struct Parser {
     traveler: Traveler,
+    errors:   Vec<Diagnostic>,
+    fixities: Vec<Fixity>,
 }
 
 impl Parser {
     pub fn new(traveler: Traveler) -> Parser {
         Parser {
             traveler,
+            errors:   Vec::new(),
+            fixities: Vec::new(),
         }
     }
 
-    pub fn parse(&mut self) -> ParserResult<Vec<Statement>> {
+    // parses the operators of `fixities`, declared by earlier input, as if this source declared them
+    pub fn with_fixities(mut self, fixities: &[Fixity]) -> Parser {
+        self.fixities.extend_from_slice(fixities);
+        self
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Statement>, Vec<Diagnostic>> {
+        let (ast, errors) = self.parse_partial();
+
+        if errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // keeps going past errors, returning whatever could be parsed along with every error found
+    pub fn parse_partial(&mut self) -> (Vec<Statement>, Vec<Diagnostic>) {
         let mut stack = Vec::new();
 
         while self.traveler.remaining() > 1 {
-            self.skip_whitespace()?;
-            stack.push(self.statement()?);
+            let top = self.traveler.top();
+
+            match self.statement() {
+                Ok(statement) => stack.push(statement),
+                Err(err)      => {
+                    self.errors.push(err);
+                    self.synchronize();
+
+                    if self.traveler.top() == top {
+                        self.traveler.next();
+                    }
+                },
+            }
         }
 
-        Ok(stack)
+        (stack, mem::take(&mut self.errors))
+    }
+
+    // skips to the start of the next top-level line, or to the `}` closing the current block
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+
+        while self.traveler.remaining() > 1 {
+            match self.traveler.current().token_type {
+                TokenType::Symbol => match self.traveler.current_content().as_str() {
+                    "{" => depth += 1,
+                    "}" => {
+                        if depth == 0 {
+                            return
+                        }
+
+                        depth -= 1
+                    },
+                    _ => (),
+                },
+
+                TokenType::EOL if depth == 0 => {
+                    self.traveler.next();
+
+                    if self.traveler.current().token_type != TokenType::Indent {
+                        return
+                    }
+
+                    continue
+                },
+
+                _ => (),
+            }
+
+            self.traveler.next();
+        }
     }
 
     pub fn skip_whitespace(&mut self) -> ParserResult<()> {
@@ -96,6 +164,9 @@ impl Parser {
 
     fn arm(&mut self) -> ParserResult<Expression> {
         self.traveler.expect_content("|")?;
+
+        let position = self.traveler.current().position;
+
         self.traveler.next();
 
         let mut params = Vec::new();
@@ -103,24 +174,65 @@ impl Parser {
         let mut acc = 0;
 
         while self.traveler.current_content() != "|" {
+            if self.traveler.remaining() < 2 {
+                return Err(Diagnostic::error(UNEXPECTED_END, self.traveler.span(), "expected '|', found end of source"))
+            }
+
+            // `|-1|` matches a negative number, overloading `-` takes a name
+            if self.traveler.current_content() == "-" && self.traveler.remaining() > 2 {
+                let next = self.traveler.get(self.traveler.top() + 1).clone();
+
+                if next.token_type == TokenType::IntLiteral || next.token_type == TokenType::FloatLiteral {
+                    params.push(Rc::new(match next.token_type {
+                        TokenType::FloatLiteral => Expression::Float(-next.content().parse::<f64>().unwrap()),
+                        _                       => Expression::Number(-next.content().parse::<i64>().unwrap()),
+                    }));
+
+                    self.traveler.next();
+                    self.traveler.next();
+
+                    acc += 1;
+
+                    continue
+                }
+            }
+
             if acc == 0 {
                 if self.traveler.current().token_type == TokenType::Operator {
-                    params.push(Rc::new(Expression::Operand(Operand::from_str(&self.traveler.current_content()).unwrap().0)));
+                    let op = match self.operand() {
+                        Some(op) => op,
+                        None     => return Err(Diagnostic::error(UNEXPECTED_PARAMETER, self.traveler.span(), &format!("unknown operator: {}", self.traveler.current_content()))),
+                    };
+
+                    params.push(Rc::new(Expression::Operand(op)));
                     self.traveler.next();
 
+                    acc += 1;
+
+                    continue
+                }
+
+                // `|[] k|` overloads indexing
+                if self.traveler.current_content() == "[" && self.traveler.remaining() > 2 && self.traveler.get(self.traveler.top() + 1).content() == "]" {
+                    params.push(Rc::new(Expression::Operand(Operand::Index)));
+                    self.traveler.next();
+                    self.traveler.next();
+
+                    acc += 1;
+
                     continue
                 }
             }
             
             let a = match self.traveler.current().token_type {
                 TokenType::IntLiteral    => {
-                    let a = Expression::Number(self.traveler.current_content().parse::<f64>().unwrap());
+                    let a = Expression::Number(self.traveler.current_content().parse::<i64>().unwrap());
                     self.traveler.next();
                     a
                 }
 
                 TokenType::FloatLiteral  => {
-                    let a = Expression::Number(self.traveler.current_content().parse::<f64>().unwrap());
+                    let a = Expression::Float(self.traveler.current_content().parse::<f64>().unwrap());
                     self.traveler.next();
                     a
                 }
@@ -149,7 +261,7 @@ impl Parser {
                     a
                 },
 
-                _ => return Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected parameter: {}", self.traveler.current_content()))),
+                _ => return Err(Diagnostic::error(UNEXPECTED_PARAMETER, self.traveler.span(), &format!("unexpected parameter: {}", self.traveler.current_content()))),
             };
 
             params.push(Rc::new(a));
@@ -164,31 +276,55 @@ impl Parser {
 
         self.skip_whitespace()?;
 
-        Ok(Expression::Arm(Arm {params, body, position: self.traveler.current().position}))
+        let doc = self.traveler.doc(position.line);
+
+        Ok(Expression::Arm(Arm {params, body, position, doc}))
     }
 
     fn function(&mut self) -> ParserResult<Expression> {
+        let open = self.traveler.span();
+
         self.traveler.next();
 
         self.skip_whitespace()?;
 
         let mut arms = Vec::new();
 
-        while self.traveler.current_content() != "}" {            
-            if self.traveler.current_content() == "|" {
-                arms.push(Statement::Expression(Rc::new(self.arm()?)))
+        while self.traveler.current_content() != "}" {
+            if self.traveler.remaining() < 2 {
+                return Err(Diagnostic::error(UNEXPECTED_END, self.traveler.span(), "expected '}', found end of source").label(open, "function starts here"))
+            }
+
+            let arm = if self.traveler.current_content() == "|" {
+                self.arm().map(|a| Some(Statement::Expression(Rc::new(a))))
             } else {
                 self.skip_whitespace()?;
                 if self.traveler.current_content() != "}" {
-                    arms.push(self.statement()?);
+                    self.statement().map(Some)
+                } else {
+                    Ok(None)
                 }
+            };
+
+            match arm {
+                Ok(Some(arm)) => arms.push(arm),
+                Ok(None)      => (),
+                Err(err)      => {
+                    self.errors.push(err);
+                    self.synchronize();
+
+                    // lost the closing brace, keep what was parsed of the body
+                    if self.traveler.current_content() != "}" {
+                        return Ok(Expression::Function(Function{arms: Rc::new(Expression::Block(arms)), position: open.start}))
+                    }
+                },
             }
         }
 
         self.traveler.expect_content("}")?;
         self.traveler.next();
 
-        Ok(Expression::Function(Function{arms: Rc::new(Expression::Block(arms)), position: self.traveler.current().position}))
+        Ok(Expression::Function(Function{arms: Rc::new(Expression::Block(arms)), position: open.start}))
     }
 
     pub fn term(&mut self) -> ParserResult<Expression> {
@@ -197,14 +333,19 @@ impl Parser {
         }
 
         match self.traveler.current().token_type {
+            TokenType::Operator => match UnaryOperand::from_str(&self.traveler.current_content()) {
+                Some(op) => self.unary(op),
+                None     => Err(Diagnostic::error(UNEXPECTED_TOKEN, self.traveler.span(), &format!("unexpected: {}", self.traveler.current_content()))),
+            },
+
             TokenType::IntLiteral    => {
-                let a = Ok(Expression::Number(self.traveler.current_content().parse::<f64>().unwrap()));
+                let a = Ok(Expression::Number(self.traveler.current_content().parse::<i64>().unwrap()));
                 self.traveler.next();
                 a
             }
 
             TokenType::FloatLiteral  => {
-                let a = Ok(Expression::Number(self.traveler.current_content().parse::<f64>().unwrap()));
+                let a = Ok(Expression::Float(self.traveler.current_content().parse::<f64>().unwrap()));
                 self.traveler.next();
                 a
             }
@@ -266,28 +407,30 @@ impl Parser {
                 }
                 "{" => self.function(),
                 _ =>{
-                    Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected symbol: {}", self.traveler.current_content())))
+                    Err(Diagnostic::error(UNEXPECTED_SYMBOL, self.traveler.span(), &format!("unexpected symbol: {}", self.traveler.current_content())))
                 }
             },
 
-            _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+            _ => Err(Diagnostic::error(UNEXPECTED_TOKEN, self.traveler.span(), &format!("unexpected: {}", self.traveler.current_content()))),
         }
     }
 
-    fn assignment(&mut self, left: Rc<Expression>) -> ParserResult<Statement> {
+    fn assignment(&mut self, left: Rc<Expression>, position: TokenPosition) -> ParserResult<Statement> {
         self.traveler.next();
 
         if self.traveler.current_content() == "\n" {
-            Err(ParserError::new_pos(self.traveler.current().position, &format!("expected expression, found: {:?}", self.traveler.current_content())))
+            Err(Diagnostic::error(EXPECTED_EXPRESSION, self.traveler.span(), &format!("expected expression, found: {:?}", self.traveler.current_content())))
         } else {
             let right = Rc::new(self.expression()?);
+            let doc   = self.traveler.doc(position.line);
 
             Ok(
                 Statement::Assignment(
                     Assignment {
                         left,
                         right,
-                        position: self.traveler.current().position
+                        position,
+                        doc,
                     }
                 )
             )
@@ -305,11 +448,17 @@ impl Parser {
                 _ => Ok(Statement::Expression(Rc::new(self.expression()?))),
             },
             TokenType::Identifier => {
-                let a = Expression::Identifier(Rc::new(self.traveler.current_content().clone()), self.traveler.current().position);
+                let position = self.traveler.current().position;
+                let name     = self.traveler.current_content();
+
+                let a = Expression::Identifier(Rc::new(name.clone()), position);
                 self.traveler.next();
 
                 if self.traveler.current_content() == "=" {
-                    self.assignment(Rc::new(a))
+                    self.assignment(Rc::new(a), position)
+                } else if FIXITY_KEYWORDS.contains(&name.as_str()) && self.traveler.current().token_type == TokenType::IntLiteral {
+                    self.traveler.prev();
+                    self.fixity()
                 } else {
                     self.traveler.prev();
                     Ok(Statement::Expression(Rc::new(self.expression()?)))
@@ -368,76 +517,143 @@ impl Parser {
     }
 
     fn operation(&mut self, expression: Expression) -> ParserResult<Expression> {
-        let mut ex_stack = vec![expression];
-        let mut op_stack: Vec<(Operand, u8)> = Vec::new();
+        self.climb(expression, 0)
+    }
+
+    // `-x`, `not x` and `bnot x`, where the operand takes in only `^` and `.`
+    fn unary(&mut self, op: UnaryOperand) -> ParserResult<Expression> {
+        let position = self.traveler.current().position;
+
+        let span     = self.traveler.span();
 
-        op_stack.push(Operand::from_str(&self.traveler.current_content()).unwrap());
         self.traveler.next();
 
         if self.traveler.current_content() == "\n" {
-            self.traveler.next();
+            return Err(Diagnostic::error(EXPECTED_EXPRESSION, span, &format!("expected expression after `{}`, found end of line", op.symbol())))
         }
 
-        let term = self.term()?;
+        let operand = self.term()?;
 
-        ex_stack.push(term);
+        if operand == Expression::EOF {
+            return Err(Diagnostic::error(UNEXPECTED_END, span, &format!("expected expression after `{}`, found end of source", op.symbol())))
+        }
 
-        let mut done = false;
+        let operand = self.climb(operand, Operand::Pow.binding().0)?;
 
-        while ex_stack.len() > 1 {
-            if !done {
-                if self.traveler.current().token_type != TokenType::Operator {
-                    done = true;
-                    continue
-                }
+        Ok(Expression::Unary(Unary { op, operand: Rc::new(operand), position }))
+    }
 
-                let (op, precedence) = Operand::from_str(&self.traveler.current_content()).unwrap();
-                self.traveler.next();
+    fn operand(&self) -> Option<Operand> {
+        if self.traveler.current().token_type != TokenType::Operator {
+            return None
+        }
 
-                if precedence >= op_stack.last().unwrap().1 {
-                    let left  = ex_stack.pop().unwrap();
-                    let right = ex_stack.pop().unwrap();
+        let symbol = self.traveler.current_content();
 
-                    ex_stack.push(
-                        Expression::Operation(
-                            Operation {
-                                right: Rc::new(left),
-                                op:    op_stack.pop().unwrap().0,
-                                left:  Rc::new(right),
-                                position: self.traveler.current().position
-                            }
-                        )
-                    );
+        Operand::from_str(&symbol).or_else(|| {
+            self.fixities.iter().find(|f| *f.symbol == symbol).map(|f| Operand::Infix(f.clone()))
+        })
+    }
 
-                    let term = self.term()?;
+    // `infixl 4 <$>` or `infixr 1 >>=`, from then on `<$>` and `>>=` parse as operators
+    fn fixity(&mut self) -> ParserResult<Statement> {
+        let position = self.traveler.current().position;
 
-                    ex_stack.push(term);
-                    op_stack.push((op, precedence));
+        let assoc = match self.traveler.current_content().as_str() {
+            "infixr" => Assoc::Right,
+            _        => Assoc::Left,
+        };
 
-                    continue
-                }
+        self.traveler.next();
+
+        let precedence = match self.traveler.current_content().parse::<u8>() {
+            Ok(p) if p <= 12 => p,
+            _ => return Err(Diagnostic::error(INVALID_FIXITY, self.traveler.span(), "precedence must be between 0 and 12")),
+        };
+
+        self.traveler.next();
+
+        if self.traveler.current().token_type != TokenType::Operator {
+            return Err(Diagnostic::error(INVALID_FIXITY, self.traveler.span(), &format!("expected operator, found: {}", self.traveler.current_content())))
+        }
+
+        let symbol = self.traveler.current_content();
+
+        if Operand::from_str(&symbol).is_some() {
+            return Err(Diagnostic::error(INVALID_FIXITY, self.traveler.span(), &format!("`{}` is a builtin operator, its fixity can't change", symbol)))
+        }
+
+        // every use of it would be lexed as a comment
+        if symbol.starts_with("--") {
+            return Err(Diagnostic::error(INVALID_FIXITY, self.traveler.span(), &format!("`{}` can't be declared, `--` starts a comment", symbol)))
+        }
+
+        if let Some(f) = self.fixities.iter().find(|f| *f.symbol == symbol) {
+            return Err(Diagnostic::error(INVALID_FIXITY, self.traveler.span(), &format!("`{}` is already declared", symbol)).label(Span::width(f.position, 1), "declared here"))
+        }
+
+        self.traveler.next();
+
+        let fixity = Fixity {
+            symbol: Rc::new(symbol),
+            precedence,
+            assoc,
+            position,
+        };
 
-                let term = self.term()?;
+        self.fixities.push(fixity.clone());
 
-                ex_stack.push(term);
-                op_stack.push((op, precedence));
+        Ok(Statement::Fixity(fixity))
+    }
+
+    // precedence climbing, folds every operator binding at least as tight as `min` into `left`
+    fn climb(&mut self, mut left: Expression, min: u8) -> ParserResult<Expression> {
+        while let Some(op) = self.operand() {
+            let (precedence, _) = op.binding();
+
+            // at the end `current` keeps returning the last token, which would be this operator again
+            if precedence < min || self.traveler.remaining() < 2 {
+                break
             }
 
-            let left  = ex_stack.pop().unwrap();
-            let right = ex_stack.pop().unwrap();
+            let position = self.traveler.current().position;
+            let span     = self.traveler.span();
 
-            ex_stack.push(
-                Expression::Operation(
-                    Operation {
-                        right: Rc::new(left),
-                        op:    op_stack.pop().unwrap().0,
-                        left:  Rc::new(right),
-                        position: self.traveler.current().position,
-                    }
-                )
-            );
+            self.traveler.next();
+
+            if self.traveler.current_content() == "\n" {
+                self.traveler.next();
+            }
+
+            let mut right = self.term()?;
+
+            if right == Expression::EOF {
+                return Err(Diagnostic::error(UNEXPECTED_END, span, &format!("expected expression after `{}`, found end of source", op.symbol())))
+            }
+
+            // tighter operators take the right side first, and so does a right associative one
+            while let Some(next) = self.operand() {
+                let (next_precedence, assoc) = next.binding();
+
+                if next_precedence > precedence {
+                    right = self.climb(right, precedence + 1)?
+                } else if next_precedence == precedence && assoc == Assoc::Right {
+                    right = self.climb(right, precedence)?
+                } else {
+                    break
+                }
+            }
+
+            left = Expression::Operation(
+                Operation {
+                    left:  Rc::new(left),
+                    op,
+                    right: Rc::new(right),
+                    position,
+                }
+            )
         }
 
-        Ok(ex_stack.pop().unwrap())
+        Ok(left)
     }
 }
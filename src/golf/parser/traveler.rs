@@ -1,19 +1,54 @@
 use super::{Token, TokenType};
-use super::{ParserError, ParserResult};
+use super::{ParserResult, Diagnostic, Span, EXPECTED_TOKEN};
+
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub struct Traveler {
     pub tokens: Vec<Token>,
     top: usize,
+    // doc comments, kept apart from the tokens being parsed
+    docs: Vec<Token>,
 }
 
 #[allow(dead_code)]
 impl Traveler {
     pub fn new(tokens: Vec<Token>) -> Traveler {
+        let (docs, tokens) = tokens.into_iter()
+                                   .filter(|t| t.token_type != TokenType::Comment)
+                                   .partition(|t| t.token_type == TokenType::DocComment);
+
         Traveler {
             tokens,
             top: 0,
+            docs,
+        }
+    }
+
+    // the doc comment lines right above `line`, without their `---`
+    pub fn doc(&self, line: usize) -> Option<Rc<String>> {
+        let mut lines = Vec::new();
+        let mut above = line;
+
+        while above > 1 {
+            above -= 1;
+
+            match self.docs.iter().find(|d| d.position.line == above) {
+                Some(doc) => {
+                    let text = &doc.content()[3 ..];
+                    lines.push(text.strip_prefix(' ').unwrap_or(text))
+                },
+                None => break,
+            }
         }
+
+        if lines.is_empty() {
+            return None
+        }
+
+        lines.reverse();
+
+        Some(Rc::new(lines.join("\n")))
     }
 
     pub fn next(&mut self) -> bool {
@@ -32,6 +67,10 @@ impl Traveler {
         false
     }
 
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
     pub fn remaining(&self) -> usize {
         self.tokens.len() - self.top + 1
     }
@@ -48,6 +87,10 @@ impl Traveler {
         &self.tokens[i]
     }
 
+    pub fn span(&self) -> Span {
+        Span::width(self.current().position, self.current().content().chars().count())
+    }
+
     pub fn current_content(&self) -> String {
         self.current().content().clone()
     }
@@ -56,7 +99,7 @@ impl Traveler {
         if self.current().token_type == token {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new_pos(self.current().position, &format!("expected '{:?}', found '{}'", token, self.current_content())))
+            Err(Diagnostic::error(EXPECTED_TOKEN, self.span(), &format!("expected '{:?}', found '{}'", token, self.current_content())))
         }
     }
 
@@ -64,7 +107,7 @@ impl Traveler {
         if self.current_content() == content {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new_pos(self.current().position, &format!("expected '{}', found '{}'", content, self.current_content())))
+            Err(Diagnostic::error(EXPECTED_TOKEN, self.span(), &format!("expected '{}', found '{}'", content, self.current_content())))
         }
     }
 
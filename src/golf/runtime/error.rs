@@ -0,0 +1,27 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RuntimeErrorValue {
+    Constant(String),
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub value: RuntimeErrorValue,
+}
+
+impl RuntimeError {
+    pub fn new(value: &str) -> RuntimeError {
+        RuntimeError {
+            value: RuntimeErrorValue::Constant(value.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            RuntimeErrorValue::Constant(ref s) => write!(f, "{}", s),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod error;
+pub mod runtime;
+
+pub use super::*;
+
+pub use self::error::*;
+pub use self::runtime::*;
+
+pub type RuntimeResult<T> = Result<T, RuntimeError>;
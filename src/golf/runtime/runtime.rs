@@ -0,0 +1,142 @@
+use super::*;
+
+use mlua::{Lua, MultiValue, Value, Variadic};
+
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+
+// globals the embedded vm provides, visible to golf code run in-process
+pub const LUA_GLOBALS: &[&str] = &[
+    "print", "tostring", "tonumber", "type", "error", "assert",
+    "pairs", "ipairs", "next", "select", "pcall",
+    "setmetatable", "getmetatable", "rawget", "rawset",
+    "table", "string", "math", "io", "os",
+    // `table.unpack` there, the transpiler names it for each target
+    "unpack",
+];
+
+pub fn prelude() -> SymTab {
+    let symtab = SymTab::new_global();
+
+    for name in LUA_GLOBALS {
+        symtab.add_name(name);
+    }
+
+    symtab
+}
+
+#[derive(Debug)]
+pub struct RunOutput {
+    pub stdout: String,
+    pub status: i32,
+    // whether the chunk called `os.exit`, whatever the status
+    pub exited: bool,
+    pub values: Vec<String>,
+    pub error:  Option<RuntimeError>,
+}
+
+pub struct Runtime {
+    lua:    Lua,
+    stdout: Rc<RefCell<String>>,
+    exit:   Rc<Cell<Option<i32>>>,
+}
+
+impl Runtime {
+    pub fn new() -> RuntimeResult<Runtime> {
+        let runtime = Runtime {
+            lua:    Lua::new(),
+            stdout: Rc::new(RefCell::new(String::new())),
+            exit:   Rc::new(Cell::new(None)),
+        };
+
+        runtime.capture().map_err(|e| RuntimeError::new(&e.to_string()))?;
+
+        Ok(runtime)
+    }
+
+    // redirects `print`, `io.write` and `os.exit` so the caller gets output and status back
+    fn capture(&self) -> mlua::Result<()> {
+        let globals = self.lua.globals();
+
+        let stdout = self.stdout.clone();
+        let print  = self.lua.create_function(move |lua, args: Variadic<Value>| {
+            let tostring: mlua::Function = lua.globals().get("tostring")?;
+
+            let mut line = Vec::new();
+
+            for arg in args {
+                line.push(tostring.call::<_, String>(arg)?)
+            }
+
+            let mut stdout = stdout.borrow_mut();
+            stdout.push_str(&line.join("\t"));
+            stdout.push('\n');
+
+            Ok(())
+        })?;
+
+        globals.set("print", print)?;
+
+        let stdout = self.stdout.clone();
+        let write  = self.lua.create_function(move |lua, args: Variadic<Value>| {
+            let tostring: mlua::Function = lua.globals().get("tostring")?;
+
+            for arg in args {
+                stdout.borrow_mut().push_str(&tostring.call::<_, String>(arg)?)
+            }
+
+            Ok(())
+        })?;
+
+        let io: mlua::Table = globals.get("io")?;
+        io.set("write", write)?;
+
+        let exit_code = self.exit.clone();
+        let exit      = self.lua.create_function(move |_, code: Value| {
+            exit_code.set(Some(match code {
+                Value::Nil | Value::Boolean(true) => 0,
+                Value::Boolean(false)             => 1,
+                Value::Integer(n)                 => n as i32,
+                Value::Number(n)                  => n as i32,
+                _                                 => 1,
+            }));
+
+            Err::<(), _>(mlua::Error::RuntimeError("exit".to_string()))
+        })?;
+
+        let os: mlua::Table = globals.get("os")?;
+        os.set("exit", exit)?;
+
+        Ok(())
+    }
+
+    // `name` is what errors and tracebacks call the chunk
+    pub fn run(&self, name: &str, source: &str) -> RunOutput {
+        let result = self.lua.load(source).set_name(format!("={}", name)).eval::<MultiValue>();
+
+        let stdout = self.stdout.replace(String::new());
+
+        if let Some(status) = self.exit.take() {
+            return RunOutput { stdout, status, exited: true, values: Vec::new(), error: None }
+        }
+
+        match result.and_then(|values| self.display(values)) {
+            Ok(values) => RunOutput { stdout, status: 0, exited: false, values, error: None },
+            Err(err)   => RunOutput { stdout, status: 1, exited: false, values: Vec::new(), error: Some(RuntimeError::new(&Runtime::message(&err))) },
+        }
+    }
+
+    fn display(&self, values: MultiValue) -> mlua::Result<Vec<String>> {
+        let tostring: mlua::Function = self.lua.globals().get("tostring")?;
+
+        values.into_iter().map(|v| tostring.call::<_, String>(v)).collect()
+    }
+
+    // the innermost cause, without the callback wrapping mlua adds
+    fn message(err: &mlua::Error) -> String {
+        match *err {
+            mlua::Error::CallbackError { ref cause, .. } => Runtime::message(cause),
+            ref e => e.to_string(),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use super::*;
+
+// a token or trivia, and the source it covers up to the next one
+enum Item {
+    Token(Token, String),
+    Trivia(Trivia),
+}
+
+// the lossless tree of a source, which `text` turns back into that exact source, with comments as trivia
+//
+// indentation stays a token rather than trivia, since the parser reads it
+pub fn concrete(source: &str) -> Result<SyntaxTree, Diagnostics> {
+    concrete_with(source, &[])
+}
+
+// with the operators of `fixities` declared ahead of the source
+pub fn concrete_with(source: &str, fixities: &[Fixity]) -> Result<SyntaxTree, Diagnostics> {
+    let chars: Vec<char> = source.chars().collect();
+
+    let mut starts = vec![0];
+
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '\n' {
+            starts.push(i + 1)
+        }
+    }
+
+    let offset = |position: TokenPosition| starts[position.line - 1] + position.col;
+
+    let position = |offset: usize| {
+        let line = starts.iter().take_while(|s| **s <= offset).count();
+        TokenPosition::new(line, offset - starts[line - 1])
+    };
+
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    let symbols: Vec<&str> = fixities.iter().map(|f| f.symbol.as_str()).collect();
+
+    for token in lexer(&mut source.chars()).with_trivia().with_operators(&symbols) {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(err)  => errors.push(err),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors)
+    }
+
+    let whitespace = |from: usize, to: usize| {
+        let text: String = chars[from .. to].iter().collect();
+
+        Item::Trivia(Trivia {
+            token: Token::new(TokenType::Whitespace, position(from), text.clone()),
+            text,
+        })
+    };
+
+    let mut items = Vec::new();
+
+    if let Some(first) = tokens.first() {
+        if offset(first.position) > 0 {
+            items.push(whitespace(0, offset(first.position)))
+        }
+    }
+
+    // the lexer leaves out whitespace, so it's whatever lies between one token and the next
+    for (i, token) in tokens.iter().enumerate() {
+        let start = offset(token.position);
+        let end   = tokens.get(i + 1).map(|t| offset(t.position)).unwrap_or(chars.len());
+
+        let split = match token.token_type {
+            TokenType::EOL | TokenType::Indent => start + token.content().chars().count(),
+            _                                  => {
+                let written = chars[start .. end].iter().rev().skip_while(|c| c.is_whitespace()).count();
+                start + written
+            },
+        };
+
+        let text: String = chars[start .. split].iter().collect();
+
+        if token.token_type == TokenType::Comment || token.token_type == TokenType::DocComment {
+            items.push(Item::Trivia(Trivia { token: token.clone(), text }))
+        } else {
+            items.push(Item::Token(token.clone(), text))
+        }
+
+        if split < end {
+            items.push(whitespace(split, end))
+        }
+    }
+
+    Ok(SyntaxTree { tokens: attach(items, position(chars.len())) })
+}
+
+// trivia on the line of the token before it trails that token, the rest leads the token after it
+fn attach(items: Vec<Item>, end: TokenPosition) -> Vec<SyntaxToken> {
+    let mut tokens: Vec<SyntaxToken> = Vec::new();
+    let mut leading   = Vec::new();
+    let mut same_line = false;
+
+    for item in items {
+        match item {
+            Item::Token(token, text) => {
+                same_line = token.token_type != TokenType::EOL;
+
+                tokens.push(SyntaxToken {
+                    token,
+                    text,
+                    leading:  std::mem::take(&mut leading),
+                    trailing: Vec::new(),
+                })
+            },
+
+            Item::Trivia(trivia) => {
+                let breaks = trivia.text.contains('\n');
+
+                if same_line {
+                    tokens.last_mut().unwrap().trailing.push(trivia)
+                } else {
+                    leading.push(trivia)
+                }
+
+                if breaks {
+                    same_line = false
+                }
+            },
+        }
+    }
+
+    tokens.push(SyntaxToken {
+        token:    Token::new(TokenType::EOF, end, String::new()),
+        text:     String::new(),
+        leading,
+        trailing: Vec::new(),
+    });
+
+    tokens
+}
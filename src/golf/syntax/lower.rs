@@ -0,0 +1,33 @@
+use super::*;
+
+// the syntax tree of a concrete tree, parsed from its tokens with the trivia left out but for doc comments
+pub fn lower(root: &SyntaxTree) -> Result<Vec<Statement>, Diagnostics> {
+    lower_with(root, &[])
+}
+
+// with the operators of `fixities` declared ahead of the source
+pub fn lower_with(root: &SyntaxTree, fixities: &[Fixity]) -> Result<Vec<Statement>, Diagnostics> {
+    let docs = |trivia: &Vec<Trivia>| {
+        trivia.iter()
+              .filter(|t| t.token.token_type == TokenType::DocComment)
+              .map(|t| t.token.clone())
+              .collect::<Vec<_>>()
+    };
+
+    let mut tokens = Vec::new();
+
+    for token in &root.tokens {
+        tokens.extend(docs(&token.leading));
+
+        if token.token.token_type != TokenType::EOF {
+            tokens.push(token.token.clone())
+        }
+
+        tokens.extend(docs(&token.trailing));
+    }
+
+    let traveler   = Traveler::new(tokens);
+    let mut parser = Parser::new(traveler).with_fixities(fixities);
+
+    parser.parse()
+}
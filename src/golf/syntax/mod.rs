@@ -0,0 +1,9 @@
+pub mod tree;
+pub mod concrete;
+pub mod lower;
+
+pub use super::*;
+
+pub use self::tree::*;
+pub use self::concrete::*;
+pub use self::lower::*;
@@ -0,0 +1,52 @@
+use super::*;
+
+// whitespace or a comment, with the exact text it was written as
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    pub token: Token,
+    pub text:  String,
+}
+
+// a token as written, with the trivia before it and the trivia after it on its own line
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    pub token:    Token,
+    pub text:     String,
+    pub leading:  Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+}
+
+impl SyntaxToken {
+    pub fn write(&self, out: &mut String) {
+        for trivia in &self.leading {
+            out.push_str(&trivia.text)
+        }
+
+        out.push_str(&self.text);
+
+        for trivia in &self.trailing {
+            out.push_str(&trivia.text)
+        }
+    }
+}
+
+// the whole file, every token as written, ending in an `EOF` token that holds the trailing trivia
+//
+// statements and brackets aren't grouped here, `lower` leaves them to the parser so there's only one reading of them
+#[derive(Debug, Clone)]
+pub struct SyntaxTree {
+    pub tokens: Vec<SyntaxToken>,
+}
+
+impl SyntaxTree {
+    // the source this was built from, byte for byte
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+
+        for token in &self.tokens {
+            token.write(&mut out)
+        }
+
+        out
+    }
+}
@@ -1,9 +1,13 @@
-pub mod error;
+mod error;
+pub mod sourcemap;
+pub mod target;
 pub mod transpiler;
 
 pub use super::*;
 
 pub use self::error::*;
+pub use self::sourcemap::*;
+pub use self::target::*;
 pub use self::transpiler::*;
 
-pub type TranspileResult<T> = Result<T, TranspileError>;
+pub type TranspileResult<T> = Result<T, Diagnostic>;
@@ -0,0 +1,98 @@
+use super::*;
+
+// brackets the index of a position inside generated code, until `SourceMap::extract` takes them out
+pub const MARK_START: char = '\u{E000}';
+pub const MARK_END:   char = '\u{E001}';
+
+// a point in the generated lua, with 1-based lines and 0-based columns like `TokenPosition`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub line:     usize,
+    pub col:      usize,
+    pub position: TokenPosition,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    // strips marks out of `marked`, remembering where in the plain output each one was
+    pub fn extract(marked: &str, positions: &[TokenPosition]) -> (String, SourceMap) {
+        let mut output   = String::new();
+        let mut mappings = Vec::new();
+
+        let mut line = 1;
+        let mut col  = 0;
+
+        let mut chars = marked.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                MARK_START => {
+                    let index: String = chars.by_ref().take_while(|c| *c != MARK_END).collect();
+
+                    if let Some(position) = index.parse::<usize>().ok().and_then(|i| positions.get(i)) {
+                        mappings.push(Mapping { line, col, position: *position })
+                    }
+                },
+
+                '\n' => {
+                    output.push(c);
+                    line += 1;
+                    col   = 0;
+                },
+
+                _ => {
+                    output.push(c);
+                    col += 1;
+                },
+            }
+        }
+
+        (output, SourceMap { mappings })
+    }
+
+    // the golf position of the first node generated on a lua line, or the closest one above it
+    pub fn lookup(&self, line: usize) -> Option<TokenPosition> {
+        let mut found: Option<&Mapping> = None;
+
+        for mapping in &self.mappings {
+            if mapping.line > line {
+                break
+            }
+
+            match found {
+                Some(f) if f.line == mapping.line => (),
+                _ => found = Some(mapping),
+            }
+        }
+
+        found.map(|m| m.position)
+    }
+
+    // turns every `chunk:line` in a lua error or traceback into `file:line` of the golf source
+    pub fn rewrite_traceback(&self, traceback: &str, chunk: &str, file: &str) -> String {
+        let pattern    = format!("{}:", chunk);
+        let mut result = String::new();
+        let mut rest   = traceback;
+
+        while let Some(i) = rest.find(&pattern) {
+            result.push_str(&rest[.. i]);
+            rest = &rest[i + pattern.len() ..];
+
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+            match rest[.. digits].parse::<usize>().ok().and_then(|line| self.lookup(line)) {
+                Some(position) => result.push_str(&format!("{}:{}", file, position.line)),
+                None           => result.push_str(&format!("{}{}", pattern, &rest[.. digits])),
+            }
+
+            rest = &rest[digits ..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
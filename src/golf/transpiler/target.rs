@@ -0,0 +1,61 @@
+use std::fmt;
+
+// the lua the transpiler writes for, each version having what the one before has
+// except luajit, which is 5.1 with `goto` and the `bit` library
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Target {
+    Lua51,
+    LuaJIT,
+    Lua53,
+    // the version the embedded vm runs
+    #[default]
+    Lua54,
+}
+
+impl Target {
+    pub const ALL: [Target; 4] = [Target::Lua51, Target::LuaJIT, Target::Lua53, Target::Lua54];
+
+    pub fn from_name(name: &str) -> Option<Target> {
+        Target::ALL.iter().find(|t| t.name() == name).cloned()
+    }
+
+    // as a manifest or `--target` names it
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Target::Lua51  => "5.1",
+            Target::LuaJIT => "luajit",
+            Target::Lua53  => "5.3",
+            Target::Lua54  => "5.4",
+        }
+    }
+
+    // integers apart from floats, with `//` and the bitwise operators built in
+    pub fn has_integers(&self) -> bool {
+        *self == Target::Lua53 || *self == Target::Lua54
+    }
+
+    // `bit.band` and the rest, where there are no bitwise operators
+    pub fn has_bit_library(&self) -> bool {
+        *self == Target::LuaJIT
+    }
+
+    pub fn has_goto(&self) -> bool {
+        *self != Target::Lua51
+    }
+
+    // `local x <const> = ...`
+    pub fn has_const(&self) -> bool {
+        *self == Target::Lua54
+    }
+
+    // whether lua looks `method` up when the operator is used on a table
+    pub fn has_metamethod(&self, method: &str) -> bool {
+        self.has_integers() || !["__idiv", "__band", "__bor", "__bxor", "__shl", "__shr", "__bnot"].contains(&method)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
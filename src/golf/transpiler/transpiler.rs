@@ -1,31 +1,177 @@
 use super::*;
 
 use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+
+// `++` takes anything, leaving values with a `__concat` to it
+const CONCAT: &str = "local function __concat(a, b)
+local meta = getmetatable(a)
+if type(meta) ~= \"table\" or not meta.__concat then meta = getmetatable(b) end
+if type(meta) == \"table\" and meta.__concat then return a .. b end
+return tostring(a) .. tostring(b)
+end
+";
+
+// declared operators are defined by arms on either operand, like lua's own metamethods
+const INFIX: &str = "local function __infix(op, a, b)
+local meta = getmetatable(a)
+if type(meta) ~= \"table\" or not meta[op] then meta = getmetatable(b) end
+if type(meta) ~= \"table\" or not meta[op] then error(\"no definition of \" .. op .. \" for \" .. tostring(a) .. \" and \" .. tostring(b), 2) end
+return meta[op](a, b)
+end
+";
+
+// the portable name, `unpack` being a global only up to 5.1
+const UNPACK: &str = "local unpack = table.unpack
+";
 
 pub struct Transpiler {
-    ast: Vec<Statement>
+    ast:          Vec<Statement>,
+    global:       bool,
+    match_errors: bool,
+    target:       Target,
+    mapping:      Cell<bool>,
+    positions:    RefCell<Vec<TokenPosition>>,
+    helpers:      RefCell<Vec<&'static str>>,
+    // the function whose arms are being written, when a call to itself in their tail can jump back to its start
+    looping:      RefCell<Option<Rc<String>>>,
+    jumped:       Cell<bool>,
 }
 
 impl Transpiler {
     pub fn new(ast: Vec<Statement>) -> Transpiler {
         Transpiler {
             ast,
+            global:       false,
+            match_errors: false,
+            target:       Target::default(),
+            mapping:      Cell::new(false),
+            positions:    RefCell::new(Vec::new()),
+            helpers:      RefCell::new(Vec::new()),
+            looping:      RefCell::new(None),
+            jumped:       Cell::new(false),
         }
     }
 
+    // for chunks run one after another in the same lua state:
+    // top-level bindings become globals and a trailing expression is returned
+    pub fn new_global(ast: Vec<Statement>) -> Transpiler {
+        Transpiler {
+            ast,
+            global:       true,
+            match_errors: false,
+            target:       Target::default(),
+            mapping:      Cell::new(false),
+            positions:    RefCell::new(Vec::new()),
+            helpers:      RefCell::new(Vec::new()),
+            looping:      RefCell::new(None),
+            jumped:       Cell::new(false),
+        }
+    }
+
+    // calls matching none of a function's arms raise a lua error, instead of returning nothing
+    pub fn set_match_errors(&mut self, match_errors: bool) {
+        self.match_errors = match_errors
+    }
+
+    pub fn set_target(&mut self, target: Target) {
+        self.target = target
+    }
+
+    // `what` needs one of the targets `supported` holds for, and this one isn't
+    fn unsupported(&self, position: TokenPosition, width: usize, what: &str, supported: fn(&Target) -> bool) -> Diagnostic {
+        let names: Vec<&str> = Target::ALL.iter().filter(|t| supported(t)).map(|t| t.name()).collect();
+
+        let (last, rest) = names.split_last().unwrap();
+        let message = format!("{} needs a target of {} or {}, not {}", what, rest.join(", "), last, self.target);
+
+        Diagnostic::error(UNSUPPORTED_FEATURE, Span::width(position, width), &message)
+    }
+
     pub fn lua(&self) -> TranspileResult<Rc<String>> {
         let mut result = String::new();
 
-        for statement in &self.ast {
-            result.push_str(&*self.lua_statement(&statement)?)
+        self.helpers.borrow_mut().clear();
+        self.looping.replace(None);
+
+        let last = self.ast.iter().rposition(|s| *s != Statement::Expression(Rc::new(Expression::EOF)));
+
+        for (i, statement) in self.ast.iter().enumerate() {
+            if self.global {
+                result.push_str(&*self.lua_global_statement(statement, Some(i) == last)?)
+            } else {
+                result.push_str(&*self.lua_statement(statement)?)
+            }
+
+            // one statement per line keeps the source map useful
+            if !result.is_empty() && !result.ends_with('\n') {
+                result.push('\n')
+            }
         }
 
-        Ok(Rc::new(result))
+        let mut helpers = self.helpers.borrow().concat();
+        helpers.push_str(&result);
+
+        Ok(Rc::new(helpers))
+    }
+
+    // like `lua`, along with where each part of the output came from in the golf source
+    pub fn lua_mapped(&self) -> TranspileResult<(Rc<String>, SourceMap)> {
+        self.mapping.set(true);
+        self.positions.borrow_mut().clear();
+
+        let marked = self.lua();
+
+        self.mapping.set(false);
+
+        let (lua, map) = SourceMap::extract(&marked?, &self.positions.borrow());
+
+        Ok((Rc::new(lua), map))
+    }
+
+    fn mark(&self, position: TokenPosition) -> String {
+        if !self.mapping.get() {
+            return String::new()
+        }
+
+        let mut positions = self.positions.borrow_mut();
+        positions.push(position);
+
+        format!("{}{}{}", MARK_START, positions.len() - 1, MARK_END)
+    }
+
+    // declares `helper` ahead of the generated code, once
+    fn helper(&self, helper: &'static str) {
+        let mut helpers = self.helpers.borrow_mut();
+
+        if !helpers.contains(&helper) {
+            helpers.push(helper)
+        }
+    }
+
+    fn lua_global_statement(&self, statement: &Statement, last: bool) -> TranspileResult<Rc<String>> {
+        match *statement {
+            Statement::Assignment(ref assignment) => {
+                let result = format!("{}{}={};\n", self.mark(assignment.position), self.lua_expression(&assignment.left)?, self.lua_assigned(assignment)?);
+                Ok(Rc::new(result))
+            },
+
+            Statement::Expression(ref expression) if last => match **expression {
+                Expression::Arm(_) | Expression::Block(_) => self.lua_statement(statement),
+                _ => Ok(Rc::new(format!("return {}\n", self.lua_expression(expression)?))),
+            },
+
+            _ => self.lua_statement(statement),
+        }
     }
 
     pub fn lua_statement(&self, statement: &Statement) -> TranspileResult<Rc<String>> {
         match *statement {
-            Statement::Expression(ref expression) => self.lua_expression(&expression),
+            Statement::Expression(ref expression) => match **expression {
+                Expression::Arm(_) | Expression::Block(_) | Expression::EOF => self.lua_expression(&expression),
+                // terminated, so a following `(...)` isn't read as a call on this one
+                _ => Ok(Rc::new(format!("{};", self.lua_expression(&expression)?))),
+            },
             Statement::Assignment(ref assignment) => {
                 match *assignment.left {
                     Expression::Identifier(ref id, ref pos) => {
@@ -44,30 +190,171 @@ impl Transpiler {
 
                         let left = Expression::Identifier(Rc::new(id), *pos);
 
-                        let result = format!("local {}={}\n", self.lua_expression(&left)?, self.lua_expression(&assignment.right)?);
+                        let result = match *assignment.right {
+                            // declared ahead, so the function's arms can refer to itself
+                            Expression::Function(_)      => format!("{0}local {1}\n{1}={2};\n", self.mark(assignment.position), self.lua_expression(&left)?, self.lua_assigned(assignment)?),
+                            // spaced, as `<const>=` reads as `<const` and `>=`
+                            _ if self.target.has_const() => format!("{}local {} <const> = {};\n", self.mark(assignment.position), self.lua_expression(&left)?, self.lua_expression(&assignment.right)?),
+                            _                            => format!("{}local {}={};\n", self.mark(assignment.position), self.lua_expression(&left)?, self.lua_expression(&assignment.right)?),
+                        };
+
                         Ok(Rc::new(result))
                     },
 
                     _ => {
-                        let result = format!("local {}={}\n", self.lua_expression(&assignment.left)?, self.lua_expression(&assignment.right)?);
+                        let result = format!("{}local {}={};\n", self.mark(assignment.position), self.lua_expression(&assignment.left)?, self.lua_expression(&assignment.right)?);
                         Ok(Rc::new(result))
                     },
                 }
             },
+
+            // only matters to the parser
+            Statement::Fixity(_) => Ok(Rc::new(String::new())),
+        }
+    }
+
+    // the right side of `assignment`, with functions named after what they're bound to
+    fn lua_assigned(&self, assignment: &Assignment) -> TranspileResult<Rc<String>> {
+        if let Expression::Function(ref function) = *assignment.right {
+            if let Expression::Identifier(ref id, _) = *assignment.left {
+                return self.lua_function(function, Some(id))
+            }
+        }
+
+        self.lua_expression(&assignment.right)
+    }
+
+    fn arms(function: &Function) -> Vec<&Arm> {
+        match *function.arms {
+            Expression::Block(ref statements) => statements.iter().filter_map(|s| match *s {
+                Statement::Expression(ref e) => match **e {
+                    Expression::Arm(ref arm) => Some(arm),
+                    _                        => None,
+                },
+                _ => None,
+            }).collect(),
+
+            _ => unreachable!(),
+        }
+    }
+
+    // `name` is what match failures call the function, when it's bound to one
+    fn lua_function(&self, function: &Function, name: Option<&str>) -> TranspileResult<Rc<String>> {
+        let mut result = format!("{}setmetatable({{}}, {{", self.mark(function.position));
+
+        // operator arms become metamethods, with the value itself in place of lua's first operand
+        for arm in Transpiler::arms(function) {
+            let op = match arm.operand() {
+                Some(op) => op,
+                None     => continue,
+            };
+
+            let method = match *op {
+                Operand::Infix(ref fixity) => format!("[\"{}\"]", fixity.symbol),
+                _ => match op.metamethod(arm.params.len() == 1) {
+                    Some(method) if !self.target.has_metamethod(method) => {
+                        let what = format!("overloading `{}`", op.symbol());
+                        return Err(self.unsupported(arm.position, 1, &what, Target::has_integers))
+                    },
+                    Some(method) => method.to_string(),
+                    None         => continue,
+                },
+            };
+
+            match arm.params.get(1) {
+                Some(other) => result.push_str(&format!("{}{} = function(_, {})\n", self.mark(arm.position), method, self.lua_expression(other)?)),
+                None        => result.push_str(&format!("{}{} = function(_)\n", self.mark(arm.position), method)),
+            }
+
+            match *arm.body {
+                Statement::Expression(ref e) => result.push_str(&format!("return {}\n", self.lua_expression(e)?)),
+                _                            => result.push_str(&self.lua_statement(&arm.body)?),
+            }
+
+            result.push_str("end,\n")
+        }
+
+        result.push_str("__call = function(...)\nlocal __args = {...}\n");
+
+        let top = result.len();
+
+        // a global can be bound to something else later, so calls to it stay calls
+        let looping = match name {
+            Some(name) if self.target.has_goto() && !self.global => Some(Rc::new(name.to_string())),
+            _                                                    => None,
+        };
+
+        let outer_looping = self.looping.replace(looping);
+        let outer_jumped  = self.jumped.replace(false);
+
+        let mut acc = 1;
+
+        let mut returns = false;
+        let mut arms    = 0;
+
+        match *function.arms {
+            Expression::Block(ref statements) => for s in statements {
+                if let Statement::Expression(ref e) = *s {
+                    match **e {
+                        Expression::Arm(ref arm) if arm.operand().is_some() => {
+                            acc += 1;
+                            continue
+                        },
+                        Expression::Arm(_) => arms += 1,
+                        _ if acc == statements.len() => {
+                            returns = true;
+                            result.push_str("return ")
+                        },
+                        _ => (),
+                    }
+                }
+
+                acc += 1;
+                result.push_str(&*self.lua_statement(s)?);
+                result.push('\n');
+            },
+
+            _ => unreachable!(),
+        }
+
+        if self.jumped.get() {
+            result.insert_str(top, "::__top::\n")
+        }
+
+        self.looping.replace(outer_looping);
+        self.jumped.set(outer_jumped);
+
+        if self.match_errors && arms > 0 && !returns {
+            let name = match name {
+                Some(name) => format!("'{}'", name),
+                None       => "function".to_string(),
+            };
+
+            let position = format!("ln {}, cl {}", function.position.line, function.position.col);
+
+            result.push_str("local __shown = {}\nfor __i = 2, #__args do __shown[#__shown + 1] = tostring(__args[__i]) end\n");
+            result.push_str(&format!("error(\"no arm of {} ({}) matches \" .. (#__args - 1) .. \" argument(s): \" .. table.concat(__shown, \", \"), 2)\n", name, position));
         }
+
+        result.push_str("end,\n");
+
+        result.push_str("})");
+
+        Ok(Rc::new(result))
     }
 
     pub fn lua_expression(&self, expression: &Expression) -> TranspileResult<Rc<String>> {
         match *expression {
             Expression::Number(ref n)        => Ok(Rc::new(format!("{}", n))),
+            // `1.0` stays a float where there are integers, rather than printing as `1`
+            Expression::Float(ref n) if self.target.has_integers() => Ok(Rc::new(format!("{:?}", n))),
+            Expression::Float(ref n)         => Ok(Rc::new(format!("{}", n))),
             Expression::Str(ref n)           => Ok(Rc::new(format!("\"{}\"", n))),
             Expression::Bool(ref n)          => Ok(Rc::new(format!("{}", n))),
             Expression::Char(ref n)          => Ok(Rc::new(format!("\"{}\"", n))),
-            Expression::Operand(ref op)      => match *op {
-                Operand::Add => Ok(Rc::new("__add".to_string())),
-                _ => Ok(Rc::new("".to_string())),
-            },
-            Expression::Identifier(ref n, _) => {
+            Expression::Identifier(ref n, position) => {
+                let mark = self.mark(position);
+
                 match n.as_str() {
                     "while"  |
                     "if"     |
@@ -78,8 +365,14 @@ impl Transpiler {
                     "end"    |
                     "for"    |
                     "self"   |
-                    "then" => Ok(Rc::new(format!("_{}", n))),
-                    _      => Ok(Rc::new(format!("{}", n))),
+                    "then" => Ok(Rc::new(format!("{}_{}", mark, n))),
+                    _      => {
+                        if n.as_str() == "unpack" && self.target.has_integers() {
+                            self.helper(UNPACK)
+                        }
+
+                        Ok(Rc::new(format!("{}{}", mark, n)))
+                    },
                 }
             },
 
@@ -87,12 +380,79 @@ impl Transpiler {
                 let left  = self.lua_expression(&operation.left)?;
                 let right = self.lua_expression(&operation.right)?;
 
-                match operation.op {
-                    Operand::Combine   => Ok(Rc::new(format!("function(__a) return {}({}(__a)) end\n", left, right))),
-                    Operand::PipeLeft  => Ok(Rc::new(format!("{}({})\n", left, right))),
-                    Operand::PipeRight => Ok(Rc::new(format!("{}({})\n", right, left))),
-                    _                  => Ok(Rc::new(format!("({}{}{})", left, operation.op.to_string(), right))),
-                }
+                let result = match operation.op {
+                    Operand::Pow     => format!("({}^{})", left, right),
+                    Operand::Mul     => format!("({}*{})", left, right),
+                    Operand::Div     => format!("({}/{})", left, right),
+                    Operand::IntDiv if self.target.has_integers() => format!("({}//{})", left, right),
+                    Operand::IntDiv  => format!("math.floor({}/{})", left, right),
+                    Operand::Mod     => format!("({}%{})", left, right),
+                    Operand::Add     => format!("({}+{})", left, right),
+                    Operand::Sub     => format!("({}-{})", left, right),
+                    Operand::Equal   => format!("({}=={})", left, right),
+                    Operand::NEqual  => format!("({}~={})", left, right),
+                    Operand::Lt      => format!("({}<{})", left, right),
+                    Operand::Gt      => format!("({}>{})", left, right),
+                    Operand::LtEqual => format!("({}<={})", left, right),
+                    Operand::GtEqual => format!("({}>={})", left, right),
+
+                    // lua's own, so the right side is only evaluated when it's needed
+                    Operand::And     => format!("({} and {})", left, right),
+                    Operand::Or      => format!("({} or {})", left, right),
+
+                    Operand::Concat  => {
+                        self.helper(CONCAT);
+                        format!("__concat({}, {})", left, right)
+                    },
+
+                    // `f . g` is `g` and then `f`
+                    Operand::Combine   => format!("(function(__a) return ({})(({})(__a)) end)", left, right),
+                    Operand::PipeLeft  => format!("({})({})", left, right),
+                    Operand::PipeRight => format!("({})({})", right, left),
+
+                    Operand::Index     => format!("({})[{}]", left, right),
+
+                    Operand::BAnd | Operand::BOr | Operand::BXor | Operand::Shl | Operand::Shr => {
+                        let (symbol, function) = match operation.op {
+                            Operand::BAnd => ("&", "band"),
+                            Operand::BOr  => ("|", "bor"),
+                            Operand::BXor => ("~", "bxor"),
+                            Operand::Shl  => ("<<", "lshift"),
+                            _             => (">>", "rshift"),
+                        };
+
+                        if self.target.has_integers() {
+                            format!("({}{}{})", left, symbol, right)
+                        } else if self.target.has_bit_library() {
+                            format!("bit.{}({}, {})", function, left, right)
+                        } else {
+                            let what = format!("`{}`", operation.op.symbol());
+                            return Err(self.unsupported(operation.position, what.len() - 2, &what, bitwise))
+                        }
+                    },
+
+                    Operand::Infix(ref fixity) => {
+                        self.helper(INFIX);
+                        format!("__infix(\"{}\", {}, {})", fixity.symbol, left, right)
+                    },
+                };
+
+                Ok(Rc::new(result))
+            },
+
+            Expression::Unary(ref unary) => {
+                let operand = self.lua_expression(&unary.operand)?;
+
+                let result = match unary.op {
+                    UnaryOperand::Neg  => format!("(-{})", operand),
+                    UnaryOperand::Not  => format!("(not {})", operand),
+
+                    UnaryOperand::BNot if self.target.has_integers()    => format!("(~{})", operand),
+                    UnaryOperand::BNot if self.target.has_bit_library() => format!("bit.bnot({})", operand),
+                    UnaryOperand::BNot => return Err(self.unsupported(unary.position, 4, "`bnot`", bitwise)),
+                };
+
+                Ok(Rc::new(result))
             },
 
             Expression::Call(ref call) => {
@@ -115,135 +475,47 @@ impl Transpiler {
                 Ok(Rc::new(result))
             },
 
-            Expression::Function(ref function) => {
-                let mut result = "setmetatable({}, {".to_string();
-                
-                match *function.arms {
-                    Expression::Block(ref statements) => for s in statements {
-                        match *s {
-                            Statement::Expression(ref e) => match **e {
-                                Expression::Arm(ref arm) => {
-                                    if let Some(p) = arm.params.get(0) {
-                                        match **p {
-                                            Expression::Operand(ref op) => {
-                                                if let Some(other) = arm.params.get(1) {
-                                                    result.push_str(&format!("{} = function(_, {})\n", &self.lua_expression(&Expression::Operand(op.clone()))?, &self.lua_expression(&other)?));
-                                                    
-                                                    match *arm.body {
-                                                        Statement::Expression(ref e) => result.push_str(&format!("return {}\n", self.lua_expression(&e)?)),
-                                                        _                            => result.push_str(&self.lua_statement(&arm.body)?),
-                                                    }
-
-                                                    result.push_str("end,\n")
-                                                }
-                                                
-                                            },
-                                            _ => ()
-                                        }
-                                    }
-                                },
-                                _ => (),
-                            },
-                            _ => (),
-                        }
-                    },
-
-                    _ => unreachable!(),
-                }
-
-                result.push_str("__call = function(...)\nlocal __args = {...}\n");
-
-                let mut acc = 1;
-
-                match *function.arms {
-                    Expression::Block(ref statements) => for s in statements {
-                        if acc == statements.len() {
-                            match *s {
-                                Statement::Expression(ref e) => match **e {
-                                    Expression::Arm(_) => (),
-                                    _                  => result.push_str("return "),
-                                },
-                                _ => (),
-                            }
-                        }
-
-                        acc += 1;
-                        result.push_str(&*self.lua_statement(s)?);
-                        result.push('\n');
-                    },
-
-                    _ => unreachable!(),
-                }
-
-                result.push_str("end,\n");
-
-                result.push_str("})");
-
+            Expression::Index(ref index) => {
+                let result = format!("({})[{}]", self.lua_expression(&index.id)?, self.lua_expression(&index.index)?);
                 Ok(Rc::new(result))
             },
 
+            Expression::Function(ref function) => self.lua_function(function, None),
+
             Expression::Arm(ref arm) => {
-                let mut result = format!("if {} == #__args then\n", arm.params.len() + 1);
-                
-                let mut acc = 2;
-                
-                for p in &arm.params {
+                let mut result = format!("{}if {} == #__args", self.mark(arm.position), arm.params.len() + 1);
+
+                for (i, p) in arm.params.iter().enumerate() {
                     match **p {
-                        ref c @ Expression::Identifier(_, _) => {
-                            match *c {
-                                Expression::Identifier(ref id, _) if !Operand::from_str(id).is_some() => {
-                                    result.push_str(&format!("local {} = __args[{}]\n", self.lua_expression(&c)?, acc))
-                                },
-                                
-                                _ => (),
-                            }
-                        }
+                        Expression::Number(_) |
+                        Expression::Float(_)  |
+                        Expression::Bool(_)   |
+                        Expression::Char(_)   |
+                        Expression::Str(_)    |
+                        Expression::Operation(_) => result.push_str(&format!(" and {} == __args[{}]", self.lua_expression(p)?, i + 2)),
                         _ => (),
                     }
-
-                    acc += 1
                 }
-                
-                acc = 2;
 
-                let mut flag = true;
+                result.push_str(" then\n");
 
-                for p in &arm.params {
+                for (i, p) in arm.params.iter().enumerate() {
                     match **p {
-                        ref c @ Expression::Number(_) |
-                        ref c @ Expression::Bool(_) |
-                        ref c @ Expression::Char(_) |
-                        ref c @ Expression::Operation { .. } |
-                        ref c @ Expression::Str(_) => {
-                            flag = false;
-
-                            result.push_str(&format!("if {} == __args[{}] then\n", self.lua_expression(c)?, acc));
-                            
-                            match *arm.body {
-                                Statement::Expression(ref expression) => match **expression {
-                                    Expression::Block(_) => (),
-                                    _ => result.push_str("return "),
-                                },
-                                _ => (),
-                            }
-
-                            result.push_str(&format!("{}\n", self.lua_statement(&arm.body)?));
-                            result.push_str("end\n");
-
-                            continue
+                        Expression::Identifier(ref id, _) if Operand::from_str(id).is_none() => {
+                            let attrib = if self.target.has_const() { " <const>" } else { "" };
+                            result.push_str(&format!("local {}{} = __args[{}]\n", self.lua_expression(p)?, attrib, i + 2))
                         },
-
                         _ => (),
                     }
-                    
-                    acc += 1;
                 }
 
-                if flag {
-                    match *arm.body {
-                        Statement::Expression(ref e) => result.push_str(&format!("return {}\n", self.lua_expression(&e)?)),
-                        _                            => (),
-                    }
+                // the first matching arm is the only one to run
+                match *arm.body {
+                    Statement::Expression(ref e) => match self.lua_tail_call(arm, e)? {
+                        Some(jump) => result.push_str(&jump),
+                        None       => result.push_str(&format!("return {}\n", self.lua_expression(e)?)),
+                    },
+                    _                            => result.push_str(&format!("{}return\n", self.lua_statement(&arm.body)?)),
                 }
 
                 result.push_str("end\n");
@@ -254,4 +526,47 @@ impl Transpiler {
             _ => Ok(Rc::new(String::new())),
         }
     }
+
+    // an arm ending in a call to its own function starts that function over with the new arguments,
+    // instead of going through `__call` again
+    fn lua_tail_call(&self, arm: &Arm, body: &Expression) -> TranspileResult<Option<String>> {
+        let call = match *body {
+            Expression::Call(ref call) => call,
+            _                          => return Ok(None),
+        };
+
+        let name = match *self.looping.borrow() {
+            Some(ref name) => name.clone(),
+            None           => return Ok(None),
+        };
+
+        let calls_itself = match *call.callee {
+            Expression::Identifier(ref id, _) => *id == name,
+            _                                 => false,
+        };
+
+        // a parameter by the same name is some other value
+        let shadowed = arm.params.iter().any(|p| match **p {
+            Expression::Identifier(ref id, _) => *id == name,
+            _                                 => false,
+        });
+
+        if !calls_itself || shadowed {
+            return Ok(None)
+        }
+
+        let mut args = vec!["__args[1]".to_string()];
+
+        for arg in &call.args {
+            args.push(self.lua_expression(arg)?.to_string())
+        }
+
+        self.jumped.set(true);
+
+        Ok(Some(format!("__args = {{{}}}\ngoto __top\n", args.join(", "))))
+    }
+}
+
+fn bitwise(target: &Target) -> bool {
+    target.has_integers() || target.has_bit_library()
 }
@@ -1,345 +1,909 @@
 extern crate colored;
+extern crate golf;
+extern crate serde_json;
 use colored::*;
 
-mod golf;
 use golf::*;
 
+use std::io;
 use std::io::prelude::*;
-use std::error::Error;
 
 use std::fs;
-use std::fs::File;
-use std::fs::metadata;
-
-use std::rc::Rc;
 
 use std::env;
-use std::path::Path;
-
-fn transpile_path(path: &str) {
-    let meta = metadata(path).unwrap();
-    
-    if meta.is_file() {
-        match file(path) {
-            Some(n) => write(path, n),
-            None    => (),
-        }
-    } else {
-        let paths = fs::read_dir(path).unwrap();
+use std::thread;
+use std::process;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use std::collections::HashMap;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ErrorFormat {
+    Human,
+    Json,
+}
 
-        for path in paths {
-            let path = format!("{}", path.unwrap().path().display());
-            let split: Vec<&str> = path.split(".").collect();
+struct Flags {
+    error_format: ErrorFormat,
+    // `--target`, over what a project's manifest says
+    target:       Option<Target>,
+}
 
-            match split.get(1) {
-                Some(n) if *n == "golf" => (),
-                _ => continue,
-            }
+// where the lua for `path` goes, next to it or at the same place under `out_dir` as it is under `root`
+fn output_path(root: &Path, path: &Path, out_dir: Option<&Path>) -> PathBuf {
+    match out_dir {
+        Some(dir) => {
+            let relative = match path.strip_prefix(root) {
+                Ok(relative) if relative != Path::new("") => relative,
+                _                                         => Path::new(path.file_name().unwrap()),
+            };
 
-            transpile_path(&format!("{}", path))
-        }
+            dir.join(relative).with_extension("lua")
+        },
+
+        None => path.with_extension("lua"),
     }
 }
 
-fn write(path: &str, data: Rc<String>) {
-    let path = Path::new(path);
-    println!("building: {}", path.display());
-
-    let split_name = path.file_name().unwrap().to_str().unwrap().split(".");
-    let split: Vec<&str> = split_name.collect();
-    
-    let parent_path = match path.parent() {
-        Some(p) => match p.file_name() {
-            Some(path) => path.to_str().unwrap(),
-            None       => ".",
+// transpiles every `.golf` file under `root`, false when any of them failed
+fn transpile_path(root: &Path, out_dir: Option<&Path>, flags: &Flags) -> bool {
+    let paths = match sources(root) {
+        Ok(paths) => paths,
+        Err(err)  => {
+            io_error(root, "read", err, flags);
+            return false
         },
-        None => ".",
     };
 
-    let output_name = format!("{}/{}.lua", parent_path, split.get(0).unwrap());
+    let mut built = true;
 
-    let mut output_file = File::create(output_name).unwrap();
-    match output_file.write_all(data.as_bytes()) {
-        Ok(_)    => (),
-        Err(why) => println!("{}", why.description())
+    for path in &paths {
+        built &= transpile_file(root, path, out_dir, flags)
     }
+
+    built
 }
 
-fn file(path: &str) -> Option<Rc<String>> {
-    let path    = Path::new(path);
-    let display = path.display();
-    
-    let mut file = match File::open(&path) {
-        Err(why) => panic!("failed to open {}: {}", display, why.description()),
-        Ok(file) => file,
+fn transpile_file(root: &Path, path: &Path, out_dir: Option<&Path>, flags: &Flags) -> bool {
+    if flags.error_format == ErrorFormat::Human {
+        println!("building: {}", path.display());
+    }
+
+    let options = Options {
+        target: flags.target.unwrap_or_default(),
+        .. Options::default()
     };
-    
-    let mut s = String::new();
-    
-    match file.read_to_string(&mut s) {
-        Err(why) => panic!("failed to read {}: {}", display, why.description()),
-        Ok(_)    => transpile(s),
+
+    match file(path, &options, flags) {
+        Some(output) => write(&output_path(root, path, out_dir), &output.lua, flags),
+        None         => false,
     }
 }
 
-fn transpile(s: String) -> Option<Rc<String>> {
-    let lexer = lexer(&mut s.chars());
+fn write(path: &Path, data: &str, flags: &Flags) -> bool {
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
 
-    let traveler   = Traveler::new(lexer.collect());
-    let mut parser = Parser::new(traveler);
+    match fs::create_dir_all(parent).and_then(|_| fs::write(path, data)) {
+        Ok(_)    => true,
+        Err(err) => {
+            io_error(path, "write", err, flags);
+            false
+        },
+    }
+}
 
-    match parser.parse() {
-        Err(err)  => match err {
-            ParserError {ref value, ref position} => {
-                match *position {
-                    Some(ref pos) => {
-                        let mut lines = s.lines();
+fn file(path: &Path, options: &Options, flags: &Flags) -> Option<Output> {
+    match fs::read_to_string(path) {
+        Ok(source) => transpile(&path.display().to_string(), &source, options, flags),
+        Err(err)   => {
+            io_error(path, "read", err, flags);
+            None
+        },
+    }
+}
 
-                        for i in 0 .. pos.line - 1 {
-                            if i == pos.line - 2 {
-                                let source_pos = format!("ln {}      | ", pos.line - 1).yellow();
-                                match lines.next() {
-                                    Some(line) => println!("{}{}", source_pos, line),
-                                    None       => unreachable!(),
-                                }
-                            } else {
-                                lines.next();
-                            }
-                        }
+// a file that couldn't be read or written, reported like any other diagnostic
+fn io_error(path: &Path, action: &str, err: io::Error, flags: &Flags) {
+    let diagnostic = Diagnostic::new(Severity::Error, IO_ERROR, &format!("failed to {} {}: {}", action, path.display(), err));
 
-                        let source_pos = format!("ln {}, cl {}| ", pos.line, pos.col).yellow();
+    report(&path.display().to_string(), "", &vec![diagnostic], flags)
+}
 
-                        match lines.next() {
-                            Some(line) => println!("{}{}", source_pos, line),
-                            None       => unreachable!(),
-                        }
+fn paint(severity: Severity, text: &str) -> ColoredString {
+    match severity {
+        Severity::Error   => text.red(),
+        Severity::Warning => text.magenta(),
+        Severity::Note    => text.cyan(),
+    }
+}
 
-                        let mut error = String::from("");
+// the line a span starts on, with the line above for context, and the span marked below it
+fn excerpt(source: &str, span: &Span, marker: &str, message: &str, severity: Severity) {
+    let lines: Vec<&str> = source.lines().collect();
 
-                        for _ in 0 .. pos.col + source_pos.len() {
-                            error.push_str(" ")
-                        }
+    let line = match lines.get(span.start.line - 1) {
+        Some(line) => line,
+        None       => return,
+    };
 
-                        error.push_str("^ ");
+    let source_pos = format!("ln {}, cl {}| ", span.start.line, span.start.col);
 
-                        match *value {
-                            ParserErrorValue::Constant(ref a) => error.push_str(a),
-                        }
+    if span.start.line > 1 {
+        let context_pos = format!("{:<width$}| ", format!("ln {}", span.start.line - 1), width = source_pos.len() - 2);
+        println!("{}{}", context_pos.yellow(), lines[span.start.line - 2]);
+    }
 
-                        println!("{}", error.red());
-                    },
+    println!("{}{}", source_pos.yellow(), line);
 
-                    None => (),
-                }
-            },
+    let width = if span.end.line == span.start.line && span.end.col > span.start.col {
+        span.end.col - span.start.col
+    } else {
+        1
+    };
+
+    let mut mark = String::new();
+
+    for _ in 0 .. span.start.col + source_pos.len() {
+        mark.push(' ')
+    }
+
+    for _ in 0 .. width {
+        mark.push_str(marker)
+    }
+
+    if !message.is_empty() {
+        mark.push(' ');
+        mark.push_str(message);
+    }
+
+    println!("{}", paint(severity, &mark));
+}
+
+fn render(source: &str, diagnostic: &Diagnostic) {
+    println!("{}", paint(diagnostic.severity, &format!("{}[{}]: {}", diagnostic.severity, diagnostic.code.id, diagnostic.message)).bold());
+
+    if let Some(ref span) = diagnostic.span {
+        excerpt(source, span, "^", "", diagnostic.severity)
+    }
+
+    for label in &diagnostic.labels {
+        excerpt(source, &label.span, "-", &label.message, Severity::Note)
+    }
+}
+
+fn report(path: &str, source: &str, diagnostics: &Diagnostics, flags: &Flags) {
+    for diagnostic in diagnostics {
+        match flags.error_format {
+            ErrorFormat::Human => render(source, diagnostic),
+            ErrorFormat::Json  => println!("{}", diagnostic.to_json(path)),
+        }
+    }
+}
+
+fn transpile(path: &str, source: &str, options: &Options, flags: &Flags) -> Option<Output> {
+    match compile(source, options) {
+        Ok(output) => {
+            report(path, source, &output.warnings, flags);
+            Some(output)
         },
-        Ok(stuff) => {
-            let mut symtab = SymTab::new_global();
-            let checker    = Checker::new(stuff.clone());
-
-            match checker.check(&mut symtab) {
-                Err(err) => match err {
-                    CheckError {ref value, ref position} => {
-                        match *position {
-                            Some(ref pos) => {
-                                let mut lines = s.lines();
-
-                                for i in 0 .. pos.line - 1 {
-                                    if i == pos.line - 2 {
-                                        let source_pos = format!("          | ").yellow();
-                                        match lines.next() {
-                                            Some(line) => println!("{}{}", source_pos, line),
-                                            None       => unreachable!(),
-                                        }
-                                    } else {
-                                        lines.next();
-                                    }
-                                }
-
-                                let source_pos = format!("ln {}, cl {}| ", pos.line, pos.col).yellow();
-
-                                match lines.next() {
-                                    Some(line) => println!("{}{}", source_pos, line),
-                                    None       => unreachable!(),
-                                }
-
-                                let mut error = String::from("");
-
-                                for _ in 0 .. pos.col + source_pos.len() {
-                                    error.push_str(" ")
-                                }
-
-                                error.push_str("^ ");
-
-                                match *value {
-                                    CheckErrorValue::Constant(ref a) => error.push_str(a),
-                                }
-                                
-                                println!("{}", error.red());
-                                
-                            },
-                            
-                            None => (),
-                        }
-                    },
-                },
+        Err(diagnostics) => {
+            report(path, source, &diagnostics, flags);
+            None
+        },
+    }
+}
 
-                _ => {
-                    let transpiler = Transpiler::new(stuff.clone());
-                    match transpiler.lua() {
-                        Err(err) => println!("{}", format!("{}", err).red()),
-                        Ok(lua)  => return Some(lua),   
-                    }
-                }
+// every `.golf` file under `path` in a stable order, or `path` itself when it's a file
+fn sources(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if fs::metadata(path)?.is_file() {
+        return Ok(vec![path.to_path_buf()])
+    }
+
+    let mut found = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?.path();
+
+        if entry.is_dir() {
+            found.extend(sources(&entry)?)
+        } else if entry.extension().and_then(|e| e.to_str()) == Some("golf") {
+            found.push(entry)
+        }
+    }
+
+    found.sort();
+
+    Ok(found)
+}
+
+// the path of a module from its root without `.golf`, like `game/player`
+fn module_name(root: &Path, path: &Path) -> String {
+    if root.is_file() {
+        path.file_stem().unwrap().to_string_lossy().to_string()
+    } else {
+        let relative = path.strip_prefix(root).unwrap().with_extension("");
+        relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/")
+    }
+}
+
+fn doc(args: &[String], flags: &Flags) {
+    let mut roots   = Vec::new();
+    let mut out_dir = "doc".to_string();
+    let mut html    = false;
+
+    for arg in args {
+        if arg == "--html" {
+            html = true
+        } else if let Some(dir) = arg.strip_prefix("--out-dir=") {
+            out_dir = dir.to_string()
+        } else {
+            roots.push(Path::new(arg))
+        }
+    }
+
+    if roots.is_empty() {
+        println!("golf doc <path>... [--html] [--out-dir=<dir>]");
+        process::exit(1)
+    }
+
+    // every file with the root its module is named from
+    let mut paths   = Vec::new();
+    let mut modules = Vec::new();
+    let mut failed  = false;
+
+    for root in roots {
+        match sources(root) {
+            Ok(found) => paths.extend(found.into_iter().map(|path| (root, path))),
+            Err(err)  => {
+                io_error(root, "read", err, flags);
+                failed = true
+            },
+        }
+    }
+
+    for &(root, ref path) in &paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err)   => {
+                io_error(path, "read", err, flags);
+                failed = true;
+                continue
+            },
+        };
+
+        // which is also where its page goes
+        let name = module_name(root, path);
+
+        match parse(&source) {
+            Ok(ast)          => modules.push(golf::doc::Module::new(&name, &ast)),
+            Err(diagnostics) => {
+                report(&path.display().to_string(), &source, &diagnostics, flags);
+                failed = true
+            },
+        }
+    }
+
+    let index = golf::doc::Index::new(&modules);
+
+    let mut pages = Vec::new();
+
+    for module in &modules {
+        if html {
+            pages.push((format!("{}.html", module.name), golf::doc::html(module, &index)))
+        } else {
+            pages.push((format!("{}.md", module.name), golf::doc::markdown(module, &index)))
+        }
+    }
+
+    if html {
+        pages.push(("index.html".to_string(), golf::doc::html_index(&modules)))
+    } else {
+        pages.push(("index.md".to_string(), golf::doc::markdown_index(&modules)))
+    }
+
+    for (name, content) in &pages {
+        let path = Path::new(&out_dir).join(name);
+
+        let written = fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, content));
+
+        match written {
+            Ok(_)    => if flags.error_format == ErrorFormat::Human {
+                println!("documenting: {}", path.display())
+            },
+            Err(err) => {
+                io_error(&path, "write", err, flags);
+                failed = true
+            },
+        }
+    }
+
+    if failed {
+        process::exit(1)
+    }
+}
+
+fn fmt(args: &[String], flags: &Flags) {
+    let mut roots = Vec::new();
+    let mut check = false;
+
+    for arg in args {
+        if arg == "--check" {
+            check = true
+        } else {
+            roots.push(Path::new(arg))
+        }
+    }
+
+    if roots.is_empty() {
+        println!("golf fmt <path>... [--check]");
+        process::exit(1)
+    }
+
+    let mut paths  = Vec::new();
+    let mut failed = false;
+
+    for root in roots {
+        match sources(root) {
+            Ok(found) => paths.extend(found),
+            Err(err)  => {
+                io_error(root, "read", err, flags);
+                failed = true
+            },
+        }
+    }
+
+    for path in &paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err)   => {
+                io_error(path, "read", err, flags);
+                failed = true;
+                continue
+            },
+        };
+
+        let formatted = match golf::format::format(&source) {
+            Ok(formatted)    => formatted,
+            Err(diagnostics) => {
+                report(&path.display().to_string(), &source, &diagnostics, flags);
+                failed = true;
+                continue
+            },
+        };
+
+        if formatted == source {
+            continue
+        }
+
+        // with `--check` nothing is written, an unformatted file is just a failure
+        if check {
+            if flags.error_format == ErrorFormat::Human {
+                println!("unformatted: {}", path.display())
+            }
+
+            failed = true
+        } else {
+            match fs::write(path, &formatted) {
+                Ok(_)    => if flags.error_format == ErrorFormat::Human {
+                    println!("formatting: {}", path.display())
+                },
+                Err(err) => {
+                    io_error(path, "write", err, flags);
+                    failed = true
+                },
             }
         }
     }
-    
-    None
+
+    if failed {
+        process::exit(1)
+    }
 }
 
-fn test() {
-    let test = r#"
-fib = {
-    |0| 0
+// the paths to build and where to put the lua, for `golf <path>` and `golf watch`
+fn build_args(args: &[String]) -> Option<(Vec<&Path>, Option<&Path>)> {
+    let mut roots   = Vec::new();
+    let mut out_dir = None;
 
-    a = 10
+    for arg in args {
+        match arg.strip_prefix("--out-dir=") {
+            Some(dir) => out_dir = Some(Path::new(dir)),
+            None      => roots.push(Path::new(arg)),
+        }
+    }
 
-    |1| 1
-    |n| (fib n - 1) + fib n - 2
+    if roots.is_empty() {
+        None
+    } else {
+        Some((roots, out_dir))
+    }
 }
 
-twice = {
-    |n| 2 * n
+// the directory with the project's `golf.toml`, the current one or the closest above it
+fn project_dir() -> Option<PathBuf> {
+    let mut dir = env::current_dir().ok()?;
+
+    loop {
+        if dir.join("golf.toml").is_file() {
+            return Some(dir)
+        }
+
+        if !dir.pop() {
+            return None
+        }
+    }
 }
 
-twice_fib = twice . fib
+// `golf build` exits with this when a module failed to build
+const BUILD_FAILED: i32 = 1;
+// and with this when the project itself is wrong, before anything is built
+const INVALID_PROJECT: i32 = 2;
+
+fn build(args: &[String], flags: &Flags) {
+    let mut profile = "debug";
+
+    for arg in args {
+        if arg == "--release" {
+            profile = "release"
+        } else if let Some(name) = arg.strip_prefix("--profile=") {
+            profile = name
+        } else {
+            println!("golf build [--release | --profile=<name>]");
+            process::exit(INVALID_PROJECT)
+        }
+    }
 
-a = twice_fib 10
-    "#;
+    let dir = match project_dir() {
+        Some(dir) => dir,
+        None      => {
+            let diagnostic = Diagnostic::new(Severity::Error, INVALID_MANIFEST, "no golf.toml in this directory or any above it");
+            report("golf.toml", "", &vec![diagnostic], flags);
+            process::exit(INVALID_PROJECT)
+        },
+    };
 
-    let lexer = lexer(&mut test.chars());
+    let manifest_path = dir.join("golf.toml");
 
-    let traveler   = Traveler::new(lexer.collect());
-    let mut parser = Parser::new(traveler);
+    let source = match fs::read_to_string(&manifest_path) {
+        Ok(source) => source,
+        Err(err)   => {
+            io_error(&manifest_path, "read", err, flags);
+            process::exit(INVALID_PROJECT)
+        },
+    };
 
-    match parser.parse() {
-        Err(err)  => match err {
-            ParserError {ref value, ref position} => {
-                match *position {
-                    Some(ref pos) => {
-                        let mut lines = test.lines();
+    let manifest = match Manifest::parse(&source) {
+        Ok(manifest)    => manifest,
+        Err(diagnostic) => {
+            report(&manifest_path.display().to_string(), &source, &vec![diagnostic], flags);
+            process::exit(INVALID_PROJECT)
+        },
+    };
 
-                        for i in 0 .. pos.line - 1 {
-                            if i == pos.line - 2 {
-                                let source_pos = format!("ln {}      | ", pos.line - 1).yellow();
-                                match lines.next() {
-                                    Some(line) => println!("{}{}", source_pos, line),
-                                    None       => unreachable!(),
-                                }
-                            } else {
-                                lines.next();
-                            }
-                        }
+    let target = flags.target.unwrap_or(manifest.target);
 
-                        let source_pos = format!("ln {}, cl {}| ", pos.line, pos.col).yellow();
+    let options = match manifest.options(profile) {
+        Some(options) => Options { target, .. options },
+        None          => {
+            let diagnostic = Diagnostic::new(Severity::Error, INVALID_MANIFEST, &format!("no profile `{}` in golf.toml", profile));
+            report(&manifest_path.display().to_string(), &source, &vec![diagnostic], flags);
+            process::exit(INVALID_PROJECT)
+        },
+    };
+
+    let started = Instant::now();
+
+    // every module, by name, with the root it's in
+    let mut modules: Vec<(String, PathBuf, PathBuf)> = Vec::new();
+    let mut invalid = false;
+
+    for root in &manifest.roots {
+        let root = dir.join(root);
+
+        match sources(&root) {
+            Ok(paths) => for path in paths {
+                modules.push((module_name(&root, &path), path, root.clone()))
+            },
+            Err(err)  => {
+                io_error(&root, "read", err, flags);
+                invalid = true
+            },
+        }
+    }
 
-                        match lines.next() {
-                            Some(line) => println!("{}{}", source_pos, line),
-                            None       => unreachable!(),
-                        }
+    // two roots with the same module would both build to the same file
+    for (i, (name, path, _)) in modules.iter().enumerate() {
+        if let Some((_, other, _)) = modules[.. i].iter().find(|m| m.0 == *name) {
+            let other   = other.strip_prefix(&dir).unwrap_or(other).display();
+            let message = format!("module `{}` is in more than one source root: {} and {}", name, other, path.strip_prefix(&dir).unwrap_or(path).display());
 
-                        let mut error = String::from("");
+            report(&manifest_path.display().to_string(), &source, &vec![Diagnostic::new(Severity::Error, INVALID_MANIFEST, &message)], flags);
+            invalid = true
+        }
+    }
 
-                        for _ in 0 .. pos.col + source_pos.len() {
-                            error.push_str(" ")
-                        }
+    for entry in &manifest.entries {
+        if !modules.iter().any(|m| m.0 == *entry) {
+            let diagnostic = Diagnostic::new(Severity::Error, INVALID_MANIFEST, &format!("entry `{}` is not a module in any source root", entry));
+            report(&manifest_path.display().to_string(), &source, &vec![diagnostic], flags);
+            invalid = true
+        }
+    }
 
-                        error.push_str("^ ");
+    if invalid {
+        process::exit(INVALID_PROJECT)
+    }
+
+    let out_dir = dir.join(&manifest.out_dir);
 
-                        match *value {
-                            ParserErrorValue::Constant(ref a) => error.push_str(a),
-                        }
+    let mut built    = 0;
+    let mut failed   = 0;
+    let mut warnings = 0;
 
-                        println!("{}", error.red());
-                    },
+    for (name, path, root) in &modules {
+        if flags.error_format == ErrorFormat::Human {
+            println!("building: {}", name)
+        }
 
-                    None => (),
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err)   => {
+                io_error(path, "read", err, flags);
+                failed += 1;
+                continue
+            },
+        };
+
+        let display = path.strip_prefix(&dir).unwrap_or(path).display().to_string();
+
+        match compile(&source, &options) {
+            Ok(output) => {
+                warnings += output.warnings.len();
+                report(&display, &source, &output.warnings, flags);
+
+                if write(&output_path(root, path, Some(&out_dir)), &output.lua, flags) {
+                    built += 1
+                } else {
+                    failed += 1
                 }
             },
+
+            Err(diagnostics) => {
+                warnings += diagnostics.iter().filter(|d| !d.is_error()).count();
+                report(&display, &source, &diagnostics, flags);
+                failed += 1
+            },
+        }
+    }
+
+    let elapsed = started.elapsed();
+
+    match flags.error_format {
+        ErrorFormat::Human => {
+            let mut summary = format!("built {} of {} module{} for lua {} ({}) in {:.2}s", built, modules.len(), if modules.len() == 1 { "" } else { "s" }, target, profile, elapsed.as_secs_f64());
+
+            if failed > 0 {
+                summary.push_str(&format!(", {} failed", failed))
+            }
+
+            if warnings > 0 {
+                summary.push_str(&format!(", {} warning{}", warnings, if warnings == 1 { "" } else { "s" }))
+            }
+
+            if failed > 0 {
+                println!("{}", summary.red().bold())
+            } else {
+                println!("{}", summary.green().bold())
+            }
+        },
+
+        ErrorFormat::Json => println!("{}", serde_json::json!({
+            "built":    built,
+            "failed":   failed,
+            "warnings": warnings,
+            "modules":  modules.len(),
+            "target":   target.name(),
+            "profile":  profile,
+        })),
+    }
+
+    if failed > 0 {
+        process::exit(BUILD_FAILED)
+    }
+}
+
+// how often `watch` looks for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+// rebuilds each `.golf` file under the given paths when it's saved, until interrupted
+fn watch(args: &[String], flags: &Flags) {
+    let (roots, out_dir) = match build_args(args) {
+        Some(args) => args,
+        None       => {
+            println!("golf watch <path>... [--out-dir=<dir>]");
+            process::exit(1)
         },
-        Ok(stuff) => {
-            println!("{:#?}", stuff);
-
-            let mut symtab = SymTab::new_global();
-            let checker    = Checker::new(stuff.clone());
-
-            match checker.check(&mut symtab) {
-                Err(err) => match err {
-                    CheckError {ref value, ref position} => {
-                        match *position {
-                            Some(ref pos) => {
-                                let mut lines = test.lines();
-
-                                for i in 0 .. pos.line - 1 {
-                                    if i == pos.line - 2 {
-                                        let source_pos = format!("          | ").yellow();
-                                        match lines.next() {
-                                            Some(line) => println!("{}{}", source_pos, line),
-                                            None       => unreachable!(),
-                                        }
-                                    } else {
-                                        lines.next();
-                                    }
-                                }
-
-                                let source_pos = format!("ln {}, cl {}| ", pos.line, pos.col).yellow();
-
-                                match lines.next() {
-                                    Some(line) => println!("{}{}", source_pos, line),
-                                    None       => unreachable!(),
-                                }
-
-                                let mut error = String::from("");
-
-                                for _ in 0 .. pos.col + source_pos.len() {
-                                    error.push_str(" ")
-                                }
-
-                                error.push_str("^ ");
-
-                                match *value {
-                                    CheckErrorValue::Constant(ref a) => error.push_str(a),
-                                }
-                                
-                                println!("{}", error.red());
-                                
-                            },
-                            
-                            None => (),
-                        }
-                    },
+    };
+
+    let mut built: HashMap<PathBuf, SystemTime> = HashMap::new();
+    // the roots that were missing when last looked at
+    let mut failed = vec![false; roots.len()];
+
+    for root in &roots {
+        if flags.error_format == ErrorFormat::Human {
+            println!("watching: {}", root.display())
+        }
+    }
+
+    loop {
+        let mut paths = Vec::new();
+
+        for (i, &root) in roots.iter().enumerate() {
+            // a missing root is reported once, and picked up again when it comes back
+            match sources(root) {
+                Ok(found) => {
+                    failed[i] = false;
+                    paths.extend(found.into_iter().map(|path| (root, path)))
+                },
+                Err(err)  => if !failed[i] {
+                    io_error(root, "read", err, flags);
+                    failed[i] = true
                 },
+            }
+        }
 
-                _ => {
-                    let transpiler = Transpiler::new(stuff.clone());
-                    match transpiler.lua() {
-                        Err(err) => println!("{}", format!("{}", err).red()),
-                        Ok(lua)  => println!("{}", lua),   
-                    }
-                }
+        built.retain(|path, _| paths.iter().any(|p| p.1 == *path));
+
+        for (root, path) in paths {
+            let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_)       => continue,
+            };
+
+            if built.get(&path) == Some(&modified) {
+                continue
             }
+
+            built.insert(path.clone(), modified);
+
+            transpile_file(root, &path, out_dir, flags);
+        }
+
+        thread::sleep(WATCH_INTERVAL)
+    }
+}
+
+fn run(path: &str, flags: &Flags) {
+    let options = Options {
+        prelude:    true,
+        source_map: true,
+        .. Options::default()
+    };
+
+    let compiled = match file(Path::new(path), &options, flags) {
+        Some(output) => output,
+        None         => process::exit(1),
+    };
+
+    let runtime = match Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(err)    => {
+            println!("{}", format!("{}", err).red());
+            process::exit(1)
+        },
+    };
+
+    let chunk  = Path::new(path).with_extension("lua").display().to_string();
+    let output = runtime.run(&chunk, &compiled.lua);
+
+    print!("{}", output.stdout);
+
+    if let Some(err) = output.error {
+        let message = match compiled.source_map {
+            Some(ref map) => map.rewrite_traceback(&err.to_string(), &chunk, path),
+            None          => err.to_string(),
+        };
+
+        println!("{}", message.red())
+    }
+
+    process::exit(output.status)
+}
+
+// number of `{` not yet closed by a `}`, ignoring braces in string literals
+fn open_braces(source: &str) -> isize {
+    let mut depth     = 0;
+    let mut delimeter = None;
+    let mut escaped   = false;
+
+    for c in source.chars() {
+        match delimeter {
+            Some(d) => {
+                if escaped {
+                    escaped = false
+                } else if c == '\\' {
+                    escaped = true
+                } else if c == d {
+                    delimeter = None
+                }
+            },
+
+            None => match c {
+                '"' | '\'' => delimeter = Some(c),
+                '{'        => depth += 1,
+                '}'        => depth -= 1,
+                _          => (),
+            },
+        }
+    }
+
+    depth
+}
+
+fn repl(flags: &Flags) {
+    let options = Options {
+        prelude:    true,
+        global:     true,
+        source_map: false,
+        .. Options::default()
+    };
+
+    let mut symtab = prelude();
+
+    let runtime = match Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(err)    => {
+            println!("{}", format!("{}", err).red());
+            process::exit(1)
+        },
+    };
+
+    let stdin      = io::stdin();
+    let mut buffer = String::new();
+
+    loop {
+        print!("{}", if buffer.is_empty() { "> " } else { ". " });
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_)          => buffer.push_str(&line),
+        }
+
+        if buffer.trim().is_empty() {
+            buffer.clear();
+            continue
+        }
+
+        if open_braces(&buffer) > 0 {
+            continue
+        }
+
+        let source = buffer.clone();
+        buffer.clear();
+
+        // names from input that doesn't compile or run don't exist in the vm, so the scope only keeps them after both
+        let mut scope = symtab.clone();
+
+        let lua = match compile_in(&source, &options, &mut scope) {
+            Ok(output)       => {
+                report("<repl>", &source, &output.warnings, flags);
+                output.lua
+            },
+            Err(diagnostics) => {
+                report("<repl>", &source, &diagnostics, flags);
+                continue
+            },
+        };
+
+        let output = runtime.run("repl", &lua);
+
+        print!("{}", output.stdout);
+
+        if let Some(err) = output.error {
+            println!("{}", format!("{}", err).red());
+            continue
+        }
+
+        symtab = scope;
+
+        if !output.values.is_empty() {
+            println!("{}", output.values.join("\t"))
+        }
+
+        if output.exited {
+            process::exit(output.status)
         }
     }
 }
 
 fn main() {
-    match env::args().nth(1) {
-        Some(a) => transpile_path(&a),
+    let mut flags = Flags {
+        error_format: ErrorFormat::Human,
+        target:       None,
+    };
+
+    let mut args = Vec::new();
+
+    for arg in env::args().skip(1) {
+        if let Some(format) = arg.strip_prefix("--error-format=") {
+            flags.error_format = match format {
+                "human" => ErrorFormat::Human,
+                "json"  => ErrorFormat::Json,
+                format  => {
+                    println!("unknown error format: {}", format);
+                    process::exit(1)
+                },
+            }
+        } else if let Some(target) = arg.strip_prefix("--target=") {
+            flags.target = match Target::from_name(target) {
+                Some(target) => Some(target),
+                None         => {
+                    let names: Vec<&str> = Target::ALL.iter().map(|t| t.name()).collect();
+                    println!("unknown target: {}, expected one of {}", target, names.join(", "));
+                    process::exit(1)
+                },
+            }
+        } else {
+            args.push(arg)
+        }
+    }
+
+    match args.first().map(|a| a.as_str()) {
+        Some("repl") => repl(&flags),
+
+        Some("run") => match args.get(1) {
+            Some(path) => run(path, &flags),
+            None       => println!("golf run <file>"),
+        },
+
+        Some("watch") => watch(&args[1 ..], &flags),
+
+        Some("build") => build(&args[1 ..], &flags),
+
+        Some("doc") => doc(&args[1 ..], &flags),
+
+        Some("fmt") => fmt(&args[1 ..], &flags),
+
+        Some("lsp") => {
+            let stdin = io::stdin();
+
+            match golf::lsp::serve(stdin.lock(), io::stdout()) {
+                Ok(code) => process::exit(code),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1)
+                },
+            }
+        },
+
+        Some(_) => match build_args(&args) {
+            Some((roots, out_dir)) => {
+                let mut built = true;
+
+                for root in roots {
+                    built &= transpile_path(root, out_dir, &flags)
+                }
+
+                if !built {
+                    process::exit(1)
+                }
+            },
+            None                   => println!("golf <path>... [--out-dir=<dir>]"),
+        },
 
         None => println!("a golf language
 
-golf <path>
+golf [--error-format=human|json] [--target=5.1|luajit|5.3|5.4] <path>... [--out-dir=<dir>]
+golf [--error-format=human|json] [--target=5.1|luajit|5.3|5.4] build [--release | --profile=<name>]
+golf [--error-format=human|json] run <file>
+golf [--error-format=human|json] [--target=5.1|luajit|5.3|5.4] watch <path>... [--out-dir=<dir>]
+golf [--error-format=human|json] doc <path>... [--html] [--out-dir=<dir>]
+golf [--error-format=human|json] fmt <path>... [--check]
+golf lsp
+golf repl
         "),
     }
 }
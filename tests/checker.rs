@@ -0,0 +1,73 @@
+extern crate golf;
+
+use golf::*;
+
+// the warnings `source` gets, by code and line
+fn warnings(source: &str) -> Vec<(Code, usize)> {
+    let ast     = parse(source).unwrap();
+    let checker = Checker::new(ast);
+
+    checker.check(&mut SymTab::new_global()).unwrap();
+
+    checker.warnings().iter().map(|w| (w.code, w.span.unwrap().start.line)).collect()
+}
+
+#[test]
+fn literal_then_wildcard() {
+    assert_eq!(warnings("f = {\n  |0| 1\n  |n| n\n}\n"), vec![]);
+}
+
+#[test]
+fn wildcard_then_literal() {
+    let source = "f = {\n  |n| n\n  |0| 1\n}\n";
+
+    assert_eq!(warnings(source), vec![(UNREACHABLE_ARM, 3)]);
+
+    // pointing at the arm that matches first
+    let checker = Checker::new(parse(source).unwrap());
+    checker.check(&mut SymTab::new_global()).unwrap();
+
+    let labels = &checker.warnings()[0].labels;
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].span.start.line, 2);
+}
+
+#[test]
+fn booleans() {
+    assert_eq!(warnings("f = {\n  |true| 1\n}\n"), vec![(NON_EXHAUSTIVE, 1)]);
+    assert_eq!(warnings("f = {\n  |true| 1\n  |false| 0\n}\n"), vec![]);
+    assert_eq!(warnings("f = {\n  |true| 1\n  |false| 0\n  |b| 2\n}\n"), vec![(UNREACHABLE_ARM, 4)]);
+}
+
+// arms taking a different number of arguments never overlap, each arity is checked on its own
+#[test]
+fn arities() {
+    assert_eq!(warnings("f = {\n  |a| a\n  |a b| b\n}\n"), vec![]);
+    assert_eq!(warnings("f = {\n  |a| a\n  |0 b| b\n}\n"), vec![(NON_EXHAUSTIVE, 1)]);
+    assert_eq!(warnings("f = {\n  |a b| a\n  |a| a\n  |0 1| 2\n}\n"), vec![(UNREACHABLE_ARM, 4)]);
+}
+
+// `1` and `1.0` are the same lua number
+#[test]
+fn integers_and_floats() {
+    assert_eq!(warnings("f = {\n  |1| 1\n  |1.0| 2\n  |n| n\n}\n"), vec![(UNREACHABLE_ARM, 3)]);
+    assert_eq!(warnings("f = {\n  |1| 1\n  |1.5| 2\n  |n| n\n}\n"), vec![]);
+}
+
+// input compiled into a lasting scope only adds its names once all of it compiles, like the repl's
+#[test]
+fn failed_input_declares_nothing() {
+    let options    = Options { global: true, target: Target::Lua51, .. Options::default() };
+    let mut symtab = SymTab::new_global();
+
+    // fails checking, and fails transpiling with `band` on 5.1
+    assert!(compile_in("a = 1\nb = c\n", &options, &mut symtab).is_err());
+    assert!(compile_in("d = 6 band 3\n", &options, &mut symtab).is_err());
+
+    for name in &["a", "b", "d"] {
+        assert_eq!(symtab.get_name(name), None, "{} was declared", name)
+    }
+
+    assert!(compile_in("e = 1\n", &options, &mut symtab).is_ok());
+    assert!(symtab.get_name("e").is_some());
+}
@@ -0,0 +1,66 @@
+extern crate golf;
+
+use golf::*;
+
+fn tokens(source: &str) -> Vec<(TokenType, String)> {
+    lexer(&mut source.chars()).with_trivia()
+                              .map(|t| t.unwrap())
+                              .map(|t| (t.token_type.clone(), t.content().clone()))
+                              .collect()
+}
+
+#[test]
+fn line_comments_keep_the_line_break() {
+    let found = tokens("a = 1 -- one\nb");
+
+    assert_eq!(found[3], (TokenType::Comment, "-- one".to_string()));
+    assert_eq!(found[4].0, TokenType::EOL);
+    assert_eq!(found[5], (TokenType::Identifier, "b".to_string()));
+}
+
+#[test]
+fn block_comments_nest() {
+    let found = tokens("a --[[ one --[[ two ]] still one ]] b");
+
+    assert_eq!(found, vec![
+        (TokenType::Identifier, "a".to_string()),
+        (TokenType::Comment,    "--[[ one --[[ two ]] still one ]]".to_string()),
+        (TokenType::Identifier, "b".to_string()),
+    ]);
+}
+
+#[test]
+fn minus_is_not_a_comment() {
+    let found = tokens("a - -1");
+
+    assert!(found.iter().all(|t| t.0 != TokenType::Comment));
+    assert_eq!(found.len(), 4);
+}
+
+#[test]
+fn comments_are_skipped_by_default() {
+    let found: Vec<TokenType> = lexer(&mut "  -- indented\nf --[[ x ]] 1".chars()).map(|t| t.unwrap().token_type).collect();
+
+    assert_eq!(found, vec![TokenType::Indent, TokenType::EOL, TokenType::Identifier, TokenType::IntLiteral]);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let errors: Vec<Diagnostic> = lexer(&mut "a --[[ --[[ ]]".chars()).filter_map(|t| t.err()).collect();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, UNTERMINATED_COMMENT);
+}
+
+// an operator starting with `--` would be a comment wherever it's used
+#[test]
+fn operators_cannot_start_a_comment() {
+    let errors = parse("infixl 4 -->\na = 1\n").unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, INVALID_FIXITY);
+    assert_eq!(errors[0].span.unwrap().start, TokenPosition::new(1, 9));
+
+    // other operators with dashes in them are fine
+    assert!(parse("infixl 4 ->-\na = 1 ->- 2\n").is_ok());
+}
@@ -0,0 +1,55 @@
+extern crate golf;
+extern crate serde_json;
+
+use golf::*;
+
+use serde_json::Value;
+
+fn json(diagnostic: &Diagnostic) -> Value {
+    serde_json::from_str(&diagnostic.to_json("main.golf")).unwrap()
+}
+
+fn keys(value: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+// what `--error-format=json` consumers read, the same keys whether or not there's a span
+#[test]
+fn json_schema() {
+    let start   = TokenPosition::new(2, 4);
+    let spanned = Diagnostic::error(UNEXPECTED_END, Span::width(start, 1), "expected expression")
+                      .label(Span::width(TokenPosition::new(1, 0), 3), "starts here");
+
+    let value = json(&spanned);
+
+    assert_eq!(keys(&value), vec!["code", "column", "end_column", "end_line", "file", "labels", "line", "message", "phase", "severity"]);
+
+    assert_eq!(value["file"],       "main.golf");
+    assert_eq!(value["phase"],      "parser");
+    assert_eq!(value["severity"],   "error");
+    assert_eq!(value["code"],       "E0105");
+    assert_eq!(value["message"],    "expected expression");
+    assert_eq!(value["line"],       2);
+    assert_eq!(value["column"],     5);
+    assert_eq!(value["end_line"],   2);
+    assert_eq!(value["end_column"], 6);
+
+    let label = &value["labels"][0];
+
+    assert_eq!(keys(label), vec!["column", "end_column", "end_line", "line", "message"]);
+    assert_eq!(label["message"], "starts here");
+    assert_eq!(label["column"],  1);
+
+    let spanless = json(&Diagnostic::new(Severity::Error, IO_ERROR, "failed to read main.golf"));
+
+    assert_eq!(keys(&spanless), keys(&value));
+
+    for key in &["line", "column", "end_line", "end_column"] {
+        assert!(spanless[*key].is_null(), "{} is {}", key, spanless[*key])
+    }
+
+    assert_eq!(spanless["phase"],  "io");
+    assert_eq!(spanless["labels"], Value::Array(Vec::new()));
+}
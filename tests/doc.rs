@@ -0,0 +1,90 @@
+extern crate golf;
+
+use golf::*;
+use golf::doc;
+
+const MATH: &str = "--- the sign of `n`, as -1, 0 or 1, see `abs`
+sign = {
+  --- zero is its own sign
+  |0| 0
+  |n| n / abs n
+}
+
+--- `n` without its sign, for `show`
+abs = {
+  |n| n
+}
+";
+
+const MAIN: &str = "--- prints the `sign` of `n`
+show = {
+  |n| print (sign n)
+}
+";
+
+fn modules() -> Vec<doc::Module> {
+    vec![
+        doc::Module::new("util/math", &parse(MATH).unwrap()),
+        doc::Module::new("main", &parse(MAIN).unwrap()),
+    ]
+}
+
+#[test]
+fn markdown() {
+    let modules = modules();
+    let index   = doc::Index::new(&modules);
+
+    assert_eq!(doc::markdown(&modules[0], &index), "# util/math
+
+<a id=\"sign\"></a>
+
+## sign
+
+the sign of `n`, as -1, 0 or 1, see [`abs`](#abs)
+
+- `|0|` zero is its own sign
+- `|n|`
+
+<a id=\"abs\"></a>
+
+## abs
+
+`n` without its sign, for [`show`](../main.md#show)
+
+- `|n|`
+");
+
+    // names documented in another module link across to it
+    assert!(doc::markdown(&modules[1], &index).contains("prints the [`sign`](util/math.md#sign) of `n`"));
+}
+
+#[test]
+fn html() {
+    let modules = modules();
+    let index   = doc::Index::new(&modules);
+
+    let page = doc::html(&modules[1], &index);
+
+    assert!(page.starts_with("<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>main</title>"));
+    assert!(page.contains("<h2 id=\"show\">show</h2>\n<p>prints the <a href=\"util/math.html#sign\"><code>sign</code></a> of <code>n</code></p>\n"));
+    assert!(page.contains("<ul>\n<li><code>|n|</code></li>\n</ul>\n"));
+
+    // from a nested module, links climb back to the root
+    let nested = doc::html(&modules[0], &index);
+
+    assert!(nested.contains("<li><code>|0|</code> zero is its own sign</li>"));
+    assert!(nested.contains("<a href=\"../main.html#show\"><code>show</code></a>"));
+}
+
+#[test]
+fn index() {
+    let modules = modules();
+
+    assert_eq!(doc::markdown_index(&modules), "# modules
+
+- [util/math](util/math.md) [`sign`](util/math.md#sign), [`abs`](util/math.md#abs)
+- [main](main.md) [`show`](main.md#show)
+");
+
+    assert!(doc::html_index(&modules).contains("<li><a href=\"main.html\">main</a> <a href=\"main.html#show\"><code>show</code></a></li>"));
+}
@@ -0,0 +1,39 @@
+extern crate golf;
+
+use golf::CHANGED_MEANING;
+use golf::format::*;
+
+#[test]
+fn indents_and_spaces_canonically() {
+    let source = "max = {\n|a b|\n        if (a<b) {\n    b\n} {\n a\n   }\n}\n";
+
+    assert_eq!(format(source).unwrap(), "max = {\n  |a b|\n    if (a < b) {\n      b\n    } {\n      a\n    }\n}\n");
+}
+
+#[test]
+fn aligns_arm_bodies() {
+    let source = "vec2 = {\n  | x y | {\n    |\"x\"| x\n    |-| vec2 (- x), (-y)\n    |[ ] k|   k\n  }\n}\n";
+
+    assert_eq!(format(source).unwrap(), "vec2 = {\n  |x y| {\n    |\"x\"|  x\n    |-|    vec2 (-x), (-y)\n    |[] k| k\n  }\n}\n");
+}
+
+#[test]
+fn keeps_comments_and_single_blank_lines() {
+    let source = "\n--- one\na = 1   -- a\n\n\n\n--[[ b\n   ]]\nb = 2\n\n";
+
+    assert_eq!(format(source).unwrap(), "--- one\na = 1 -- a\n\n--[[ b\n   ]]\nb = 2\n");
+}
+
+#[test]
+fn formatted_code_is_left_alone() {
+    let source = "fib = {\n  |0| 0\n  |1| 1\n  |n| fib (n - 1) + fib (n - 2)\n}\n";
+
+    assert_eq!(format(source).unwrap(), source);
+}
+
+#[test]
+fn refuses_to_change_meaning() {
+    let diagnostics = format("f = { |x| x }\ny = f  1\n").unwrap_err();
+
+    assert_eq!(diagnostics[0].code, CHANGED_MEANING);
+}
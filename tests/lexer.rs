@@ -0,0 +1,63 @@
+extern crate golf;
+
+use golf::*;
+
+// every token lexed, and every error with where it is, in source order
+fn lex(source: &str) -> (Vec<String>, Vec<(Code, Span)>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    for token in lexer(&mut source.chars()) {
+        match token {
+            Ok(token) => tokens.push(token.content().clone()),
+            Err(err)  => errors.push((err.code, err.span.unwrap())),
+        }
+    }
+
+    (tokens, errors)
+}
+
+fn span(line: usize, col: usize, width: usize) -> Span {
+    Span::width(TokenPosition::new(line, col), width)
+}
+
+// a string left open takes the rest of the source, pointed at by its opening quote
+#[test]
+fn unterminated_string() {
+    let (tokens, errors) = lex("a = 1\nb = \"abc\nc = 2\n");
+
+    assert_eq!(errors, vec![(UNTERMINATED_STRING, span(2, 4, 1))]);
+    assert_eq!(tokens, vec!["a", "=", "1", "\n", "b", "="]);
+}
+
+// the literal is skipped to its closing quote, so what follows lexes as code again
+#[test]
+fn bad_escape() {
+    let (tokens, errors) = lex("a = \"x\\qy\" ++ b\n");
+
+    assert_eq!(errors, vec![(INVALID_ESCAPE, span(1, 6, 2))]);
+    assert_eq!(tokens, vec!["a", "=", "++", "b", "\n"]);
+}
+
+#[test]
+fn stray_character() {
+    let (tokens, errors) = lex("a = 1 $ b\n");
+
+    assert_eq!(errors, vec![(UNEXPECTED_CHARACTER, span(1, 6, 1))]);
+    assert_eq!(tokens, vec!["a", "=", "1", "b", "\n"]);
+}
+
+// each error is reported once, and lexing goes on to find the next
+#[test]
+fn errors_one_after_another() {
+    let (tokens, errors) = lex("a = $\nb = \"\\q\"\nc = 'xy'\nd = `\n");
+
+    assert_eq!(errors, vec![
+        (UNEXPECTED_CHARACTER, span(1, 4, 1)),
+        (INVALID_ESCAPE,       span(2, 5, 2)),
+        (INVALID_CHAR,         span(3, 4, 4)),
+        (UNEXPECTED_CHARACTER, span(4, 4, 1)),
+    ]);
+
+    assert_eq!(tokens.iter().filter(|t| *t == "=").count(), 4);
+}
@@ -0,0 +1,177 @@
+extern crate golf;
+extern crate serde_json;
+
+use serde_json::Value;
+
+use std::io::Cursor;
+
+const SOURCE: &str = "if = {
+  |true body _| body!
+  |false _ body| body!
+}
+
+--- the larger one
+max = {
+  |a b| if (a < b), { b }, { a }
+}
+
+print (max 1, 2)
+";
+
+fn frame(messages: &[Value]) -> Vec<u8> {
+    let mut input = String::new();
+
+    for message in messages {
+        let body = message.to_string();
+        input.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body))
+    }
+
+    input.into_bytes()
+}
+
+// the replies to `requests`, after opening `source`
+fn session(source: &str, requests: &[Value]) -> (i32, Vec<Value>) {
+    let mut messages = vec![
+        serde_json::json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": "file:///a.golf", "languageId": "golf", "version": 1, "text": source },
+        }}),
+    ];
+
+    messages.extend(requests.iter().cloned());
+    messages.push(serde_json::json!({ "jsonrpc": "2.0", "id": 99, "method": "shutdown" }));
+    messages.push(serde_json::json!({ "jsonrpc": "2.0", "method": "exit" }));
+
+    serve(frame(&messages))
+}
+
+// the exit code and every message sent back for `input`
+fn serve(input: Vec<u8>) -> (i32, Vec<Value>) {
+    let mut output = Vec::new();
+    let code = golf::lsp::serve(Cursor::new(input), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+
+    let replies = output.split("Content-Length: ")
+                        .filter(|m| !m.is_empty())
+                        .map(|m| serde_json::from_str(&m[m.find("\r\n\r\n").unwrap() + 4 ..]).unwrap())
+                        .collect();
+
+    (code, replies)
+}
+
+fn request(id: u64, method: &str, line: u64, character: u64) -> Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": {
+        "textDocument": { "uri": "file:///a.golf" },
+        "position":     { "line": line, "character": character },
+        "context":      { "includeDeclaration": true },
+    }})
+}
+
+fn reply(replies: &[Value], id: u64) -> Value {
+    replies.iter().find(|r| r["id"] == id).unwrap()["result"].clone()
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let (code, replies) = session("a = b\n", &[]);
+
+    assert_eq!(code, 0);
+
+    let published = replies.iter().find(|r| r["method"] == "textDocument/publishDiagnostics").unwrap();
+    let diagnostic = &published["params"]["diagnostics"][0];
+
+    assert_eq!(diagnostic["severity"], 1);
+    assert_eq!(diagnostic["message"], "undeclared use: b");
+    assert_eq!(diagnostic["range"]["start"], serde_json::json!({ "line": 0, "character": 4 }));
+}
+
+#[test]
+fn finds_definitions_and_references() {
+    let (_, replies) = session(SOURCE, &[
+        request(1, "textDocument/definition", 10, 8),
+        request(2, "textDocument/references", 7, 3),
+        request(3, "textDocument/definition", 7, 22),
+    ]);
+
+    let published = replies.iter().find(|r| r["method"] == "textDocument/publishDiagnostics").unwrap();
+
+    assert_eq!(published["params"]["diagnostics"], serde_json::json!([]));
+
+    assert_eq!(reply(&replies, 1)["range"]["start"], serde_json::json!({ "line": 6, "character": 0 }));
+    assert_eq!(reply(&replies, 2).as_array().unwrap().len(), 3);
+    assert_eq!(reply(&replies, 3)["range"]["start"], serde_json::json!({ "line": 7, "character": 5 }));
+}
+
+#[test]
+fn hovers_with_arm_patterns() {
+    let (_, replies) = session(SOURCE, &[request(1, "textDocument/hover", 10, 8)]);
+
+    assert_eq!(reply(&replies, 1)["contents"]["value"], "```golf\nmax = {\n  |a b|\n}\n```\nthe larger one");
+}
+
+#[test]
+fn completes_names_in_scope() {
+    let (_, replies) = session(SOURCE, &[
+        request(1, "textDocument/completion", 7, 22),
+        request(2, "textDocument/completion", 10, 0),
+    ]);
+
+    let labels = |id| reply(&replies, id).as_array().unwrap().iter().map(|i| i["label"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+
+    assert!(labels(1).contains(&"a".to_string()));
+    assert!(labels(1).contains(&"max".to_string()));
+    assert!(!labels(2).contains(&"a".to_string()));
+    assert!(labels(2).contains(&"print".to_string()));
+}
+
+// an edit that doesn't parse keeps the symbols from before, moved past it, for lookups away from it
+#[test]
+fn keeps_symbols_through_edits_that_dont_parse() {
+    let change = serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+        "textDocument":   { "uri": "file:///a.golf", "version": 2 },
+        "contentChanges": [{ "text": format!("x = (\n\n\n{}", SOURCE) }],
+    }});
+
+    let (_, replies) = session(SOURCE, &[
+        request(1, "textDocument/definition", 10, 8),
+        change,
+        request(2, "textDocument/definition", 13, 8),
+        request(3, "textDocument/hover", 13, 8),
+        request(4, "textDocument/references", 10, 3),
+        request(5, "textDocument/hover", 0, 0),
+    ]);
+
+    assert_eq!(reply(&replies, 1)["range"]["start"], serde_json::json!({ "line": 6, "character": 0 }));
+    assert_eq!(reply(&replies, 2)["range"]["start"], serde_json::json!({ "line": 9, "character": 0 }));
+    assert_eq!(reply(&replies, 3)["range"]["start"], serde_json::json!({ "line": 13, "character": 7 }));
+    assert_eq!(reply(&replies, 4).as_array().unwrap().len(), 3);
+
+    // nothing is known about the lines being edited
+    assert_eq!(reply(&replies, 5), Value::Null);
+}
+
+// a message that can't be read is skipped, answered with a parse error when its id can be made out
+#[test]
+fn skips_bad_messages() {
+    let broken = "{\"jsonrpc\": \"2.0\", \"id\": 5, \"method\": \"initialize\", ";
+
+    let mut input = String::new();
+    input.push_str("Content-Type: application/json\r\n\r\n{\"jsonrpc\": \"2.0\", \"id\": 4, \"method\": \"initialize\"}");
+    input.push_str(&format!("Content-Length: {}\r\n\r\n{}", broken.len(), broken));
+
+    let mut input = input.into_bytes();
+
+    input.extend(frame(&[
+        serde_json::json!({ "jsonrpc": "2.0", "id": 6, "method": "initialize", "params": {} }),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 99, "method": "shutdown" }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]));
+
+    let (code, replies) = serve(input);
+
+    assert_eq!(code, 0);
+    assert!(replies.iter().all(|r| r["id"] != 4));
+    assert_eq!(replies.iter().find(|r| r["id"] == 5).unwrap()["error"]["code"], -32700);
+    assert_eq!(reply(&replies, 6)["serverInfo"]["name"], "golf");
+}
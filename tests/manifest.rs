@@ -0,0 +1,80 @@
+extern crate golf;
+
+use golf::*;
+
+use std::path::PathBuf;
+
+#[test]
+fn defaults() {
+    let manifest = Manifest::parse("").unwrap();
+
+    assert_eq!(manifest.roots, vec![PathBuf::from("src")]);
+    assert_eq!(manifest.out_dir, PathBuf::from("build"));
+    assert_eq!(manifest.target, Target::Lua54);
+    assert!(manifest.options("debug").unwrap().match_errors);
+    assert!(!manifest.options("release").unwrap().match_errors);
+    assert!(manifest.options("fast").is_none());
+}
+
+#[test]
+fn reads_the_project_and_profiles() {
+    let manifest = Manifest::parse(r#"
+[project]
+name    = "game"
+roots   = ["src", "lib"]
+entries = ["main"]
+out-dir = "out"
+target  = "luajit"
+
+[profile.debug]
+prelude = true
+
+[profile.ship]
+match-errors = false
+"#).unwrap();
+
+    assert_eq!(manifest.name, "game");
+    assert_eq!(manifest.roots, vec![PathBuf::from("src"), PathBuf::from("lib")]);
+    assert_eq!(manifest.entries, vec!["main".to_string()]);
+    assert_eq!(manifest.out_dir, PathBuf::from("out"));
+    assert_eq!(manifest.target, Target::LuaJIT);
+
+    let ship = manifest.options("ship").unwrap();
+
+    assert!(ship.prelude);
+    assert!(!ship.match_errors);
+}
+
+#[test]
+fn points_at_wrong_values() {
+    let err = Manifest::parse("[project]\nname = \"game\"\ntarget = \"5.2\"\n").unwrap_err();
+
+    assert_eq!(err.code, INVALID_MANIFEST);
+    assert_eq!(err.span.unwrap().start, TokenPosition::new(3, 0));
+
+    let err = Manifest::parse("[profile.release]\nmatch-errors = \"no\"\n").unwrap_err();
+
+    assert_eq!(err.message, "`match-errors` must be true or false");
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    let err = Manifest::parse("[project]\nsources = [\"src\"]\n").unwrap_err();
+
+    assert_eq!(err.message, "unknown key `sources` in `project`");
+    assert!(Manifest::parse("[package]\n").is_err());
+    assert!(Manifest::parse("[project\n").is_err());
+}
+
+// the same key in several sections, each error pointing into its own
+#[test]
+fn points_into_the_right_section() {
+    let source = "[profile.release]\nmatch-errors = false\n\n[profile.ship]\nmatch-errors = \"no\"\n";
+    let err    = Manifest::parse(source).unwrap_err();
+
+    assert_eq!(err.span.unwrap().start, TokenPosition::new(5, 0));
+
+    let err = Manifest::parse("[project]\nname = \"game\"\n\n[package]\nname = \"other\"\n").unwrap_err();
+
+    assert_eq!(err.span.unwrap().start, TokenPosition::new(4, 1));
+}
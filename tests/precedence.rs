@@ -0,0 +1,203 @@
+extern crate golf;
+
+use golf::*;
+
+// the expected grouping of every operator, tightest first, independent of the parser's own table
+const LEVELS: &[(&[&str], Assoc)] = &[
+    (&["."],                  Assoc::Right),
+    (&["^"],                  Assoc::Right),
+    (&["*", "/", "//", "%"],  Assoc::Left),
+    (&["+", "-"],             Assoc::Left),
+    (&["++"],                 Assoc::Right),
+    (&["band", "bor", "bxor", "shl", "shr"], Assoc::Left),
+    (&["<", ">", "<=", ">="], Assoc::Left),
+    (&["==", "~="],           Assoc::Left),
+    (&["and"],                Assoc::Left),
+    (&["or"],                 Assoc::Left),
+    (&["|>"],                 Assoc::Left),
+    (&["<|"],                 Assoc::Right),
+];
+
+fn level(op: &str) -> (usize, Assoc) {
+    for (i, &(ops, assoc)) in LEVELS.iter().enumerate() {
+        if ops.contains(&op) {
+            return (LEVELS.len() - i, assoc)
+        }
+    }
+
+    panic!("operator missing from the corpus: {}", op)
+}
+
+fn tree(expression: &Expression) -> String {
+    match *expression {
+        Expression::Identifier(ref id, _) => id.to_string(),
+        Expression::Number(ref n)         => n.to_string(),
+        Expression::Operation(ref o)      => format!("({} {} {})", tree(&o.left), o.op.symbol(), tree(&o.right)),
+        Expression::Unary(ref u)          => format!("({} {})", u.op.symbol(), tree(&u.operand)),
+        ref e                             => format!("{:?}", e),
+    }
+}
+
+fn parse(source: &str) -> String {
+    let tokens = lexer(&mut source.chars()).collect::<Result<Vec<Token>, Diagnostic>>().unwrap();
+    let ast    = Parser::new(Traveler::new(tokens)).parse().unwrap();
+
+    match *ast.last().unwrap() {
+        Statement::Expression(ref e) => tree(e),
+        ref s                        => panic!("expected an expression, found {:?}", s),
+    }
+}
+
+#[test]
+fn single_operators() {
+    let corpus = [
+        ("a - b - c",    "((a - b) - c)"),
+        ("a / b / c",    "((a / b) / c)"),
+        ("a ^ b ^ c",    "(a ^ (b ^ c))"),
+        ("a ++ b ++ c",  "(a ++ (b ++ c))"),
+        ("a . b . c",    "(a . (b . c))"),
+        ("a |> b |> c",  "((a |> b) |> c)"),
+        ("a <| b <| c",  "(a <| (b <| c))"),
+        ("a == b == c",  "((a == b) == c)"),
+    ];
+
+    for &(source, expected) in &corpus {
+        assert_eq!(parse(source), expected, "parsing `{}`", source)
+    }
+}
+
+#[test]
+fn mixed_operators() {
+    let corpus = [
+        ("a + b * c",          "(a + (b * c))"),
+        ("a * b + c",          "((a * b) + c)"),
+        ("a - b * c ^ d",      "(a - (b * (c ^ d)))"),
+        ("a ^ b * c - d",      "(((a ^ b) * c) - d)"),
+        ("a + b < c * d",      "((a + b) < (c * d))"),
+        ("a < b == c > d",     "((a < b) == (c > d))"),
+        ("a ++ b + c",         "(a ++ (b + c))"),
+        ("a |> f + g",         "(a |> (f + g))"),
+        ("a + b |> f |> g",    "(((a + b) |> f) |> g)"),
+        ("f <| a |> g",        "(f <| (a |> g))"),
+        ("a |> f . g",         "(a |> (f . g))"),
+        ("a or b and c",       "(a or (b and c))"),
+        ("a < b and b < c",    "((a < b) and (b < c))"),
+        ("not a or b",         "((not a) or b)"),
+        ("a and b |> f",       "((a and b) |> f)"),
+        ("a * b - c * d - e",  "(((a * b) - (c * d)) - e)"),
+    ];
+
+    for &(source, expected) in &corpus {
+        assert_eq!(parse(source), expected, "parsing `{}`", source)
+    }
+}
+
+// `a p b q c` for every pair of operators `p` and `q`
+#[test]
+fn operator_pairs() {
+    let ops: Vec<&str> = LEVELS.iter().flat_map(|&(ops, _)| ops.iter().cloned()).collect();
+
+    for p in &ops {
+        for q in &ops {
+            let (p_level, assoc) = level(p);
+            let (q_level, _)     = level(q);
+
+            let expected = if p_level > q_level || (p_level == q_level && assoc == Assoc::Left) {
+                format!("((a {} b) {} c)", p, q)
+            } else {
+                format!("(a {} (b {} c))", p, q)
+            };
+
+            let source = format!("a {} b {} c", p, q);
+
+            assert_eq!(parse(&source), expected, "parsing `{}`", source)
+        }
+    }
+}
+
+#[test]
+fn declared_operators() {
+    let corpus = [
+        ("infixl 4 <$>\na <$> b <$> c",          "((a <$> b) <$> c)"),
+        ("infixr 1 >>=\na >>= b >>= c",          "(a >>= (b >>= c))"),
+        ("infixl 4 <$>\na + b <$> c * d",        "((a + b) <$> (c * d))"),
+        ("infixr 9 <.>\na * b <.> c",            "(a * (b <.> c))"),
+        ("infixl 2 <|>\na <|> b |> f",           "((a <|> b) |> f)"),
+        ("infixl 4 <$>\ninfixr 1 >>=\na <$> b >>= c <$> d", "((a <$> b) >>= (c <$> d))"),
+    ];
+
+    for &(source, expected) in &corpus {
+        assert_eq!(parse(source), expected, "parsing `{}`", source)
+    }
+}
+
+#[test]
+fn unary_operators() {
+    let corpus = [
+        ("-a",            "(- a)"),
+        ("- -a",          "(- (- a))"),
+        ("-a ^ b",        "(- (a ^ b))"),
+        ("-a * b",        "((- a) * b)"),
+        ("a - -b",        "(a - (- b))"),
+        ("a -1",          "(a - 1)"),
+        ("a - 1",         "(a - 1)"),
+        ("-(a + b)",      "(- (a + b))"),
+        ("not a",         "(not a)"),
+        ("not a == b",    "((not a) == b)"),
+        ("a == not b",    "(a == (not b))"),
+        ("-a . b",        "(- (a . b))"),
+        ("-a |> f",       "((- a) |> f)"),
+        ("bnot a band b", "((bnot a) band b)"),
+    ];
+
+    for &(source, expected) in &corpus {
+        assert_eq!(parse(source), expected, "parsing `{}`", source)
+    }
+}
+
+// an operator with nothing after it, at the very end or before a last newline
+#[test]
+fn missing_right_operand() {
+    for source in &["a = 1 +", "y = f |>", "y = 1 +\n", "x = 1 + 2 *"] {
+        let errors = compile(source, &Options::default()).err().unwrap_or_else(|| panic!("compiled `{}`", source));
+
+        assert_eq!(errors[0].code, UNEXPECTED_END, "compiling `{:?}`", source);
+        assert_eq!(errors[0].span.unwrap().start.line, 1)
+    }
+}
+
+#[test]
+fn missing_unary_operand() {
+    let corpus = [
+        ("x = -",       UNEXPECTED_END),
+        ("x = not",     UNEXPECTED_END),
+        ("x = 1 + -",   UNEXPECTED_END),
+        ("x = bnot\n",  EXPECTED_EXPRESSION),
+        ("x = not\ny = 1\n", EXPECTED_EXPRESSION),
+    ];
+
+    for &(source, code) in &corpus {
+        let errors = compile(source, &Options::default()).err().unwrap_or_else(|| panic!("compiled `{}`", source));
+
+        assert_eq!(errors[0].code, code, "compiling `{:?}`", source)
+    }
+}
+
+// declarations carry over between inputs compiled into the same scope, like the repl's
+#[test]
+fn declared_across_inputs() {
+    let options = Options { global: true, .. Options::default() };
+    let mut symtab = SymTab::new_global();
+
+    compile_in("infixr 1 >>=\na = 1\n", &options, &mut symtab).unwrap();
+
+    let output = compile_in("a >>= a >>= a\n", &options, &mut symtab).unwrap();
+
+    match output.ast[0] {
+        Statement::Expression(ref e) => assert_eq!(tree(e), "(a >>= (a >>= a))"),
+        ref s                        => panic!("expected an expression, found {:?}", s),
+    }
+
+    // in a scope of its own the operator is unknown
+    assert!(compile_in("a >>= a\n", &options, &mut SymTab::new_global()).is_err());
+}
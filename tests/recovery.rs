@@ -0,0 +1,62 @@
+extern crate golf;
+
+use golf::*;
+
+const SOURCE: &str = "a = )
+b = 2
+c = (1 + )
+d = {
+  |x| x +
+}
+e = 5
+";
+
+fn partial(source: &str) -> (Vec<Statement>, Vec<Diagnostic>) {
+    let tokens = lexer(&mut source.chars()).collect::<Result<Vec<Token>, Diagnostic>>().unwrap();
+
+    Parser::new(Traveler::new(tokens)).parse_partial()
+}
+
+fn bound(ast: &[Statement]) -> Vec<String> {
+    ast.iter().filter_map(|s| match *s {
+        Statement::Assignment(ref a) => match *a.left {
+            Expression::Identifier(ref id, _) => Some(id.to_string()),
+            _                                 => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+// every independent error is reported, each where it is
+#[test]
+fn every_error() {
+    let errors = parse(SOURCE).unwrap_err();
+
+    let found: Vec<(Code, usize, usize)> = errors.iter().map(|e| {
+        let span = e.span.unwrap();
+        (e.code, span.start.line, span.start.col)
+    }).collect();
+
+    assert_eq!(found, vec![
+        (UNEXPECTED_SYMBOL, 1, 4),
+        (UNEXPECTED_SYMBOL, 3, 9),
+        (UNEXPECTED_SYMBOL, 6, 0),
+    ]);
+}
+
+#[test]
+fn parsing_carries_on() {
+    let (ast, errors) = partial(SOURCE);
+
+    // `d` is kept, the bad arm being skipped up to its `}`
+    assert_eq!(errors.len(), 3);
+    assert_eq!(bound(&ast), vec!["b", "d", "e"]);
+}
+
+#[test]
+fn valid_source() {
+    let (ast, errors) = partial("a = 1\nb = a + 1\n");
+
+    assert!(errors.is_empty());
+    assert_eq!(bound(&ast), vec!["a", "b"]);
+}
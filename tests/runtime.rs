@@ -0,0 +1,127 @@
+extern crate golf;
+
+use golf::*;
+
+// compiles `source` the way `golf run` does and runs it in a fresh vm
+fn run_with(source: &str, match_errors: bool) -> RunOutput {
+    let options = Options {
+        prelude: true,
+        match_errors,
+        .. Options::default()
+    };
+
+    let output = compile(source, &options).unwrap();
+
+    Runtime::new().unwrap().run("test", &output.lua)
+}
+
+fn run(source: &str) -> RunOutput {
+    run_with(source, true)
+}
+
+fn error(output: RunOutput) -> String {
+    output.error.expect("expected a runtime error").to_string()
+}
+
+#[test]
+fn exit() {
+    let output = run("print \"before\"\nquit = os[\"exit\"]\nquit!\nprint \"after\"\n");
+
+    assert_eq!(output.stdout, "before\n");
+    assert!(output.exited);
+    assert_eq!(output.status, 0);
+
+    let output = run("quit = os[\"exit\"]\nquit 3\n");
+
+    assert!(output.exited);
+    assert_eq!(output.status, 3);
+
+    assert!(!run("print 1\n").exited);
+}
+
+#[test]
+fn match_failures() {
+    let source = "f = {\n  |0| \"zero\"\n}\nprint (f 0)\nprint (f 1, 2)\n";
+
+    let output = run(source);
+    assert_eq!(output.stdout, "zero\n");
+    assert!(error(output).contains("no arm of 'f' (ln 1, cl 4) matches 2 argument(s): 1, 2"));
+
+    // without match errors a call matching nothing returns nothing
+    let output = run_with(source, false);
+    assert_eq!(output.stdout, "zero\n\n");
+    assert!(output.error.is_none());
+
+    // an unbound function is named by where it's written
+    let output = run("print (({\n  |true| 1\n}) false)\n");
+    assert!(error(output).contains("no arm of function (ln 1, cl 8) matches 1 argument(s): false"));
+}
+
+const VEC: &str = "vec = {
+  |x y| {
+    |\"x\"| x
+    |\"y\"| y
+
+    |+ v| vec (x + v \"x\"), (y + v \"y\")
+    |* k| vec (x * k), (y * k)
+    |% k| vec (x % k), (y % k)
+    |-| vec (-x), (-y)
+    |< v| x < v \"x\"
+    |<= v| x <= v \"x\"
+    |== v| x == (v \"x\") and y == (v \"y\")
+    |++ v| \"vec\"
+    |[] k| k
+  }
+}
+
+a = vec 1, 2
+b = vec 3, 4
+";
+
+// operator arms are the value's metamethods
+#[test]
+fn metamethods() {
+    let output = run(&format!("{}{}", VEC, "c = -(a + b) * 2 % 5
+print (c \"x\"), (c \"y\")
+print (a < b), (b < a), (a <= b), (a > b), (a >= b)
+print (a == vec 1, 2), (a ~= b)
+print (a ++ b), (a[\"key\"])
+"));
+
+    assert!(output.error.is_none(), "{:?}", output.error);
+    assert_eq!(output.stdout, "2\t3\ntrue\tfalse\ttrue\tfalse\tfalse\ntrue\ttrue\nvec\tkey\n");
+
+    // operators without an arm are lua errors, and some can't have one
+    assert!(error(run(&format!("{}{}", VEC, "print (a / 2)\n"))).contains("attempt to perform arithmetic"));
+
+    let options = Options { prelude: true, .. Options::default() };
+    let err = compile("v = {\n  |~= w| true\n}\n", &options).unwrap_err();
+
+    assert_eq!(err[0].code, UNSUPPORTED_OVERLOAD);
+}
+
+#[test]
+fn concat_coercion() {
+    let output = run("print (1 ++ 2.5)\nprint (\"x\" ++ true ++ false)\nbox = {\n  |v| v\n}\nprint (\"t: \" ++ box)\n");
+    let lines: Vec<&str> = output.stdout.lines().collect();
+
+    assert!(output.error.is_none(), "{:?}", output.error);
+    assert_eq!(lines[0], "12.5");
+    assert_eq!(lines[1], "xtruefalse");
+    assert!(lines[2].starts_with("t: "), "{}", lines[2]);
+}
+
+#[test]
+fn pipes() {
+    let source = "inc = {\n  |n| n + 1\n}\ndouble = {\n  |n| n * 2\n}\nprint (1 |> inc |> double)\nprint <| double <| inc <| 1\nprint <| 1 |> double |> inc\n";
+
+    assert_eq!(run(source).stdout, "4\n4\n3\n");
+}
+
+// the right side of `and` and `or` only runs when the left doesn't decide
+#[test]
+fn short_circuits() {
+    let source = "loud = {\n  |x| print \"loud\"\n}\nprint (false and (loud 1))\nprint (true or (loud 1))\nprint (1 == 2 and (loud 1) or 3)\nprint (true and false or (loud 1))\n";
+
+    assert_eq!(run(source).stdout, "false\ntrue\n3\nloud\nnil\n");
+}
@@ -0,0 +1,61 @@
+extern crate golf;
+
+use golf::*;
+
+const SOURCE: &str = "a = 1
+b = a + 1
+
+fail = {
+  |n| error (\"bad \" ++ n)
+}
+
+fail b
+";
+
+fn compiled() -> Output {
+    let options = Options {
+        prelude:    true,
+        source_map: true,
+        .. Options::default()
+    };
+
+    compile(SOURCE, &options).unwrap()
+}
+
+#[test]
+fn marks_are_extracted() {
+    let output = compiled();
+    let map    = output.source_map.unwrap();
+
+    assert!(!output.lua.contains(MARK_START) && !output.lua.contains(MARK_END));
+
+    // every golf line with code on it has lua mapped back to it
+    let lines: Vec<usize> = map.mappings.iter().map(|m| m.position.line).collect();
+
+    for line in &[1, 2, 4, 5] {
+        assert!(lines.contains(line), "nothing maps to line {}: {:?}", line, map.mappings)
+    }
+
+    // and each mapping points at the lua line holding what was generated for it
+    let lua: Vec<&str> = output.lua.lines().collect();
+    let b = map.mappings.iter().find(|m| m.position.line == 2).unwrap();
+
+    assert!(lua[b.line - 1].contains("local b"), "{}", lua[b.line - 1]);
+    assert_eq!(map.lookup(b.line).unwrap().line, 2);
+}
+
+#[test]
+fn tracebacks_are_rewritten() {
+    let output  = compiled();
+    let map     = output.source_map.unwrap();
+    let run     = Runtime::new().unwrap().run("main.lua", &output.lua);
+    let message = run.error.unwrap().to_string();
+
+    assert!(message.contains("main.lua:"), "{}", message);
+
+    let rewritten = map.rewrite_traceback(&message, "main.lua", "main.golf");
+
+    assert!(rewritten.contains("main.golf:5: bad 2"), "{}", rewritten);
+    assert!(rewritten.contains("main.golf:8: in main chunk"), "{}", rewritten);
+    assert!(!rewritten.contains("main.lua:"), "{}", rewritten);
+}
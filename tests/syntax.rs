@@ -0,0 +1,58 @@
+extern crate golf;
+
+use golf::*;
+
+fn roundtrip(source: &str) {
+    assert_eq!(concrete(source).unwrap().text(), source);
+}
+
+#[test]
+fn regenerates_the_source() {
+    roundtrip(include_str!("../samples/fib.golf"));
+    roundtrip(include_str!("../samples/meta.golf"));
+    roundtrip(include_str!("../samples/flow.golf"));
+
+    roundtrip("");
+    roundtrip("  \n\n");
+    roundtrip("a = 1");
+    roundtrip("\ta =  (1+2)   -- sum  \r\n--[[ a\n  --[[ b ]] ]]\tb = \"x  y\"   \n\n");
+    roundtrip("f = {\n\t|x|   x  \n  |\"y\" 'c'| -1.5\n}\n--- end");
+}
+
+#[test]
+fn trivia_trails_on_its_line_and_leads_otherwise() {
+    let root   = concrete("a -- one\n-- two\nb").unwrap();
+    let tokens = &root.tokens;
+
+    let texts = |trivia: &Vec<Trivia>| trivia.iter().map(|t| t.text.clone()).collect::<Vec<_>>();
+
+    assert_eq!(tokens[0].text, "a");
+    assert_eq!(texts(&tokens[0].trailing), vec![" ", "-- one"]);
+
+    let b = tokens.iter().find(|t| t.text == "b").unwrap();
+
+    assert_eq!(texts(&b.leading), Vec::<String>::new());
+    assert!(tokens.iter().any(|t| texts(&t.leading) == vec!["-- two"]));
+}
+
+// doc comments are trivia like any other, which lowering still reads the docs from
+#[test]
+fn doc_comments_are_trivia() {
+    let root = concrete("--- one\na = 1\n").unwrap();
+
+    assert!(root.tokens.iter().all(|t| t.token.token_type != TokenType::DocComment));
+    assert_eq!(root.tokens[0].leading[0].token.token_type, TokenType::DocComment);
+    assert_eq!(root.tokens[0].leading[0].text, "--- one");
+
+    match lower(&root).unwrap()[0] {
+        Statement::Assignment(ref a) => assert_eq!(a.doc.as_ref().map(|d| d.as_str()), Some("one")),
+        ref s                        => panic!("expected an assignment, found {:?}", s),
+    }
+}
+
+#[test]
+fn lowers_to_the_syntax_tree() {
+    let ast = lower(&concrete("a = 1\n\nb = { |x| x + a }\n").unwrap()).unwrap();
+
+    assert_eq!(ast.len(), 2);
+}
@@ -0,0 +1,80 @@
+extern crate golf;
+
+use golf::*;
+
+fn lua(source: &str, target: Target) -> Result<String, Diagnostics> {
+    let options = Options {
+        prelude:      true,
+        match_errors: false,
+        target,
+        .. Options::default()
+    };
+
+    compile(source, &options).map(|output| output.lua.to_string())
+}
+
+#[test]
+fn operators() {
+    let source = "a = 7 // 2\nb = 6 band 3 bor 8\nc = bnot 0\n";
+
+    let lua53 = lua(source, Target::Lua53).unwrap();
+    assert!(lua53.contains("(7//2)"), "{}", lua53);
+    assert!(lua53.contains("((6&3)|8)"), "{}", lua53);
+    assert!(lua53.contains("(~0)"), "{}", lua53);
+
+    let luajit = lua(source, Target::LuaJIT).unwrap();
+    assert!(luajit.contains("math.floor(7/2)"), "{}", luajit);
+    assert!(luajit.contains("bit.bor(bit.band(6, 3), 8)"), "{}", luajit);
+    assert!(luajit.contains("bit.bnot(0)"), "{}", luajit);
+
+    let err = lua(source, Target::Lua51).unwrap_err();
+    assert_eq!(err[0].code, UNSUPPORTED_FEATURE);
+    assert_eq!(err[0].span.unwrap().start, TokenPosition::new(2, 6));
+}
+
+#[test]
+fn literals_and_locals() {
+    let source = "a = 1.0 + 2\n";
+
+    assert_eq!(lua(source, Target::Lua54).unwrap(), "local a <const> = (1.0+2);\n");
+    assert_eq!(lua(source, Target::Lua53).unwrap(), "local a=(1.0+2);\n");
+    assert_eq!(lua(source, Target::Lua51).unwrap(), "local a=(1+2);\n");
+
+    // integers are written as they are, not through a float
+    assert_eq!(lua("a = 9007199254740993\n", Target::Lua54).unwrap(), "local a <const> = 9007199254740993;\n");
+    assert_eq!(lua("a = 9223372036854775807\n", Target::Lua53).unwrap(), "local a=9223372036854775807;\n");
+
+    let err = lua("a = 9223372036854775808\n", Target::Lua53).unwrap_err();
+    assert_eq!(err[0].code, INVALID_NUMBER);
+    assert_eq!(err[0].span.unwrap().start.line, 1);
+}
+
+// a function calling itself last jumps back to its start, where there's `goto`
+#[test]
+fn tail_calls() {
+    let source = "count = {\n  |0| 0\n  |n| count (n - 1)\n}\n";
+
+    for &target in &[Target::LuaJIT, Target::Lua53, Target::Lua54] {
+        let lua = lua(source, target).unwrap();
+
+        assert!(lua.contains("::__top::"), "{}", lua);
+        assert!(lua.contains("__args = {__args[1], (n-1)}\ngoto __top"), "{}", lua);
+    }
+
+    let lua51 = lua(source, Target::Lua51).unwrap();
+    assert!(!lua51.contains("goto"), "{}", lua51);
+    assert!(lua51.contains("return (count)((n-1))"), "{}", lua51);
+}
+
+#[test]
+fn unpack_and_overloads() {
+    let source = "f = unpack\n";
+
+    assert!(lua(source, Target::Lua53).unwrap().starts_with("local unpack = table.unpack\n"));
+    assert!(!lua(source, Target::LuaJIT).unwrap().contains("table.unpack"));
+
+    let overload = "box = {\n  |v| {\n    |band k| box v\n  }\n}\n";
+
+    assert!(lua(overload, Target::Lua54).unwrap().contains("__band = function(_, k)"));
+    assert_eq!(lua(overload, Target::LuaJIT).unwrap_err()[0].code, UNSUPPORTED_FEATURE);
+}